@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::digest::name;
+use crate::formula::Formula;
+use crate::simplification::miniscope;
+use crate::symbol::Symbol;
+use crate::term::Term;
+
+use Formula::*;
+
+/// Disjunctions which would expand to more clauses have operands named
+const NAMING_THRESHOLD: usize = 32;
+
+type Clause = Vec<Id<Formula>>;
+
+/// Negation normal form of `f`, or of its negation if not `positive`:
+/// only literals, `And`, `Or` and quantifiers remain.
+fn nnf(f: &Id<Formula>, positive: bool) -> Id<Formula> {
+    let all = |ps: &IdSet<Formula>, positive| {
+        ps.into_iter()
+            .map(|p| nnf(p, positive))
+            .collect::<IdSet<_>>()
+    };
+    match **f {
+        T | F => Id::new(if (**f == T) == positive { T } else { F }),
+        Eq(_) | Prd(_, _) => {
+            if positive {
+                f.clone()
+            } else {
+                Formula::negate(f)
+            }
+        }
+        Not(ref p) => nnf(p, !positive),
+        Imp(ref p, ref q) => {
+            let p = nnf(p, !positive);
+            let q = nnf(q, positive);
+            Id::new(if positive {
+                Or(idset![p, q])
+            } else {
+                And(idset![p, q])
+            })
+        }
+        Or(ref ps) | And(ref ps) => {
+            let ps = all(ps, positive);
+            Id::new(match (&**f, positive) {
+                (Or(_), true) | (And(_), false) => Or(ps),
+                _ => And(ps),
+            })
+        }
+        // all true or all false, or else some true and some false
+        Eqv(ref ps) => {
+            let (trues, falses) = (all(ps, true), all(ps, false));
+            Id::new(if positive {
+                Or(idset![Id::new(And(trues)), Id::new(And(falses))])
+            } else {
+                And(idset![Id::new(Or(trues)), Id::new(Or(falses))])
+            })
+        }
+        All(ref p) | Ex(ref p) => {
+            let p = nnf(p, positive);
+            Id::new(match (&**f, positive) {
+                (All(_), true) | (Ex(_), false) => All(p),
+                _ => Ex(p),
+            })
+        }
+    }
+}
+
+fn variables(f: &Id<Formula>) -> Vec<usize> {
+    let mut variables = Formula::breadth_first_terms(f)
+        .flat_map(Term::breadth_first)
+        .filter_map(|t| match **t {
+            Term::Var(n) => Some(n),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    variables.sort_unstable();
+    variables.dedup();
+    variables
+}
+
+/// Replaces quantifiers by variables and Skolem functions.
+///
+/// Variables of the result are numbered globally, not by de Bruijn index.
+#[derive(Default)]
+struct Skolemiser {
+    fresh: usize,
+    /// the terms bound by enclosing quantifiers, innermost last
+    bound: Vec<Id<Term>>,
+}
+
+impl Skolemiser {
+    fn resolve(&self, t: &Id<Term>) -> Id<Term> {
+        match **t {
+            Term::Var(n) => self.bound[self.bound.len() - 1 - n].clone(),
+            Term::Fn(ref f, ref ts) => Id::new(Term::Fn(
+                f.clone(),
+                ts.iter().map(|t| self.resolve(t)).collect(),
+            )),
+        }
+    }
+
+    /// Skolemise `p` with variable 0 bound to `t`.
+    fn bind(&mut self, p: &Id<Formula>, t: Id<Term>) -> Id<Formula> {
+        self.bound.push(t);
+        let p = self.skolemise(p);
+        self.bound.pop();
+        p
+    }
+
+    fn skolemise(&mut self, f: &Id<Formula>) -> Id<Formula> {
+        match **f {
+            And(ref ps) => Id::new(And(ps
+                .into_iter()
+                .map(|p| self.skolemise(p))
+                .collect())),
+            Or(ref ps) => {
+                Id::new(Or(ps.into_iter().map(|p| self.skolemise(p)).collect()))
+            }
+            All(ref p) => {
+                let variable = Id::new(Term::Var(self.fresh));
+                self.fresh += 1;
+                self.bind(p, variable)
+            }
+            // a function of whatever the free variables are bound to
+            Ex(ref p) => {
+                let arguments = (0..self.bound.len())
+                    .rev()
+                    .filter(|n| Formula::occurs(f, *n))
+                    .map(|n| self.bound[self.bound.len() - 1 - n].clone())
+                    .collect();
+                let symbol = Id::new(Symbol::Introduced(name(p)));
+                self.bind(p, Id::new(Term::Fn(symbol, arguments)))
+            }
+            _ => Formula::map_terms(f, &mut |t| self.resolve(t)),
+        }
+    }
+}
+
+/// Clauses of `f`, which is quantifier-free and in negation normal form.
+///
+/// Operands of large disjunctions are replaced by new predicates, defined in
+/// `definitions`.
+fn clauses(f: &Id<Formula>, definitions: &mut Vec<Clause>) -> Vec<Clause> {
+    match **f {
+        T => vec![],
+        F => vec![vec![]],
+        And(ref ps) => ps
+            .into_iter()
+            .flat_map(|p| clauses(p, definitions))
+            .collect(),
+        Or(ref ps) => {
+            let mut operands = ps
+                .into_iter()
+                .map(|p| (p, clauses(p, definitions)))
+                .collect::<Vec<_>>();
+            loop {
+                let product =
+                    operands.iter().fold(1usize, |product, (_, cs)| {
+                        product.saturating_mul(cs.len())
+                    });
+                let (largest, (p, cs)) = operands
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, (_, cs))| cs.len())
+                    .unwrap();
+                if product <= NAMING_THRESHOLD || cs.len() <= 1 {
+                    break;
+                }
+                let arguments = variables(p)
+                    .into_iter()
+                    .map(|n| Id::new(Term::Var(n)))
+                    .collect();
+                let symbol = Id::new(Symbol::Introduced(name(p)));
+                let name = Id::new(Prd(symbol, arguments));
+                for c in cs {
+                    let mut definition = vec![Formula::negate(&name)];
+                    definition.extend(c.iter().cloned());
+                    definitions.push(definition);
+                }
+                operands[largest] = (p, vec![vec![name]]);
+            }
+
+            let mut distributed = vec![vec![]];
+            for (_, cs) in operands {
+                distributed = distributed
+                    .iter()
+                    .flat_map(|d: &Clause| {
+                        cs.iter().map(move |c| {
+                            d.iter().chain(c.iter()).cloned().collect()
+                        })
+                    })
+                    .collect();
+            }
+            distributed
+        }
+        _ => vec![vec![f.clone()]],
+    }
+}
+
+/// Universally close the disjunction of `literals`, numbering variables in
+/// order of occurrence.
+///
+/// `None` if the disjunction is a tautology.
+pub fn close(literals: Clause) -> Option<Id<Formula>> {
+    let mut numbering = HashMap::new();
+    let literals: IdSet<Formula> = literals
+        .iter()
+        .map(|l| {
+            Formula::map_terms(l, &mut |t| {
+                Term::map_variables(t, &mut |n| {
+                    let next = numbering.len();
+                    *numbering.entry(n).or_insert(next)
+                })
+            })
+        })
+        .collect();
+    let tautology = literals
+        .as_ref()
+        .iter()
+        .any(|l| literals.contains(&Formula::negate(l)));
+    if tautology {
+        return None;
+    }
+
+    let mut closed = match literals.len() {
+        0 => Id::new(F),
+        1 => literals.into_iter().next().unwrap(),
+        _ => Id::new(Or(literals)),
+    };
+    for _ in 0..numbering.len() {
+        closed = Id::new(All(closed));
+    }
+    Some(closed)
+}
+
+/// Clauses equisatisfiable with the closed formula `f`, each a universally
+/// closed disjunction of literals.
+pub fn clausify(f: &Id<Formula>) -> Vec<Id<Formula>> {
+    let f = miniscope(&nnf(f, true));
+    let f = Skolemiser::default().skolemise(&f);
+    let mut definitions = vec![];
+    let mut clauses = clauses(&f, &mut definitions);
+    clauses.extend(definitions);
+
+    let mut seen = HashSet::new();
+    clauses
+        .into_iter()
+        .filter_map(close)
+        .filter(|clause| seen.insert(clause.clone()))
+        .collect()
+}
+
+/// The conjunction of the clauses of `f`, for search.
+///
+/// This is not simplified again, and simplification does not miniscope
+/// clausified problems: that would take the clauses back out of clausal
+/// form, hiding them from resolution and paramodulation.
+pub fn clausal_form(f: &Id<Formula>) -> Id<Formula> {
+    let clauses = clausify(f);
+    if clauses.iter().any(|clause| **clause == F) {
+        return Id::new(F);
+    }
+    match clauses.len() {
+        0 => Id::new(T),
+        1 => clauses.into_iter().next().unwrap(),
+        _ => Id::new(And(clauses.into_iter().collect())),
+    }
+}
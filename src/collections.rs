@@ -3,6 +3,8 @@ use std::fmt;
 use std::iter::FromIterator;
 use unique::Id;
 
+use crate::digest::{identify, Digest};
+
 pub type IdList<T> = SmallVec<[Id<T>; 8]>;
 
 macro_rules! idlist {
@@ -36,9 +38,14 @@ macro_rules! idset {
     }};
 }
 
-impl<T> IdSet<T> {
+/// The order of elements in an `IdSet`, ties broken by address.
+fn order<T: Digest>(element: &Id<T>) -> (usize, usize) {
+    (identify(element), Id::id(element))
+}
+
+impl<T: Digest> IdSet<T> {
     fn search(&self, element: &Id<T>) -> Result<usize, usize> {
-        self.elements.binary_search_by_key(&Id::id(element), Id::id)
+        self.elements.binary_search_by_key(&order(element), order)
     }
 
     pub unsafe fn from_sorted_list(mut sorted: IdList<T>) -> Self {
@@ -123,7 +130,7 @@ impl<T> IdSet<T> {
     }
 }
 
-impl<'a, T: 'a + Clone> IdSet<T> {
+impl<'a, T: 'a + Clone + Digest> IdSet<T> {
     pub fn combine_overlapping<I: IntoIterator<Item = &'a IdSet<T>>>(
         iter: I,
     ) -> Vec<IdSet<T>> {
@@ -145,15 +152,15 @@ impl<'a, T: 'a + Clone> IdSet<T> {
     }
 }
 
-impl<T> Default for IdSet<T> {
+impl<T: Digest> Default for IdSet<T> {
     fn default() -> Self {
         IdSet::empty()
     }
 }
 
-impl<T> From<IdList<T>> for IdSet<T> {
+impl<T: Digest> From<IdList<T>> for IdSet<T> {
     fn from(mut list: IdList<T>) -> Self {
-        list.sort_unstable_by_key(Id::id);
+        list.sort_unstable_by_key(order);
         list.dedup();
         Self { elements: list }
     }
@@ -165,15 +172,15 @@ impl<T> AsRef<IdList<T>> for IdSet<T> {
     }
 }
 
-impl<T> Extend<Id<T>> for IdSet<T> {
+impl<T: Digest> Extend<Id<T>> for IdSet<T> {
     fn extend<I: IntoIterator<Item = Id<T>>>(&mut self, iter: I) {
         self.elements.extend(iter);
-        self.elements.sort_unstable_by_key(Id::id);
+        self.elements.sort_unstable_by_key(order);
         self.elements.dedup();
     }
 }
 
-impl<T> FromIterator<Id<T>> for IdSet<T> {
+impl<T: Digest> FromIterator<Id<T>> for IdSet<T> {
     fn from_iter<I>(iterator: I) -> Self
     where
         I: IntoIterator<Item = Id<T>>,
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use unique::Id;
+
+use crate::deduction::{Deduced, Rule};
+use crate::formula::Formula;
+use crate::term::Term;
+use crate::unification::Substitution;
+
+use Formula::*;
+
+/// A conjunct in clause form: `literals` under `variables` universals
+pub(super) struct Clause {
+    pub(super) variables: usize,
+    pub(super) literals: Vec<Id<Formula>>,
+}
+
+impl Clause {
+    /// The literals of `right`, renamed apart from `self`
+    pub(super) fn rename_apart(&self, right: &Clause) -> Vec<Id<Formula>> {
+        let offset = self.variables;
+        right
+            .literals
+            .iter()
+            .map(|l| {
+                Formula::map_terms(l, &mut |t| {
+                    Term::map_variables(t, &mut |n| n + offset)
+                })
+            })
+            .collect()
+    }
+}
+
+/// The atom of literal `l`, and whether it is positive
+pub(super) fn atom(l: &Id<Formula>) -> Option<(bool, &Id<Formula>)> {
+    match **l {
+        Prd(_, _) | Eq(_) => Some((true, l)),
+        Not(ref p) => match **p {
+            Prd(_, _) | Eq(_) => Some((false, p)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn clause(f: &Id<Formula>) -> Option<Clause> {
+    let mut variables = 0;
+    let mut body = f;
+    while let All(ref p) = **body {
+        variables += 1;
+        body = p;
+    }
+    let literals: Vec<_> = match **body {
+        Or(ref ps) => ps.into_iter().cloned().collect(),
+        _ => vec![body.clone()],
+    };
+    if literals.iter().all(|l| atom(l).is_some()) {
+        Some(Clause {
+            variables,
+            literals,
+        })
+    } else {
+        None
+    }
+}
+
+/// The conjuncts of `f` which are clauses
+pub(super) fn clauses(f: &Id<Formula>) -> Vec<Clause> {
+    match **f {
+        And(ref ps) => ps.into_iter().filter_map(clause).collect(),
+        _ => vec![],
+    }
+}
+
+/// Apply `substitution` to all of `literals` but those at `except`.
+pub(super) fn instantiate(
+    substitution: &Substitution,
+    literals: &[Id<Formula>],
+    except: &[usize],
+) -> Vec<Id<Formula>> {
+    literals
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !except.contains(i))
+        .map(|(_, l)| Formula::map_terms(l, &mut |t| substitution.apply(t)))
+        .collect()
+}
+
+/// Add each new clause to the conjunction `f` as a separate inference.
+pub(super) fn add_clauses(
+    deduced: &mut Deduced,
+    rule: Rule,
+    f: &Id<Formula>,
+    new: HashSet<Id<Formula>>,
+) {
+    let ps = match **f {
+        And(ref ps) => ps,
+        _ => return,
+    };
+    for clause in new {
+        if !ps.contains(&clause) {
+            deduced.insert(rule, idset![Id::new(And(ps.with(clause)))]);
+        }
+    }
+}
@@ -2,33 +2,107 @@ use std::collections::HashSet;
 use unique::Id;
 
 use crate::collections::IdSet;
+use crate::deduction::{Deduced, Rule};
+use crate::digest::name;
 use crate::formula::Formula;
+use crate::ordering;
 use crate::symbol::Symbol;
+use crate::term::Term;
+use crate::unification::unify_atoms;
 
 use Formula::*;
 
 fn introduced(f: &Id<Formula>) -> Id<Symbol> {
-    Id::new(Symbol::Introduced(Id::id(f)))
+    Id::new(Symbol::Introduced(name(f)))
+}
+
+/// Atoms of `f`, with the polarity in which they occur and the number of
+/// quantifiers they are under
+fn atoms<'a>(
+    found: &mut Vec<(bool, &'a Id<Formula>, usize)>,
+    f: &'a Id<Formula>,
+    polarity: bool,
+    depth: usize,
+) {
+    match **f {
+        T | F => {}
+        Prd(_, _) | Eq(_) => found.push((polarity, f, depth)),
+        Not(ref p) => atoms(found, p, !polarity, depth),
+        Imp(ref p, ref q) => {
+            atoms(found, p, !polarity, depth);
+            atoms(found, q, polarity, depth);
+        }
+        Or(ref ps) | And(ref ps) => {
+            for p in ps {
+                atoms(found, p, polarity, depth);
+            }
+        }
+        Eqv(ref ps) => {
+            for p in ps {
+                atoms(found, p, true, depth);
+                atoms(found, p, false, depth);
+            }
+        }
+        All(ref p) | Ex(ref p) => atoms(found, p, polarity, depth + 1),
+    }
+}
+
+fn literal(f: &Id<Formula>) -> Option<(bool, &Id<Formula>)> {
+    match **f {
+        Prd(_, _) | Eq(_) => Some((true, f)),
+        Not(ref p) => match **p {
+            Prd(_, _) | Eq(_) => Some((false, p)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Terms for the variable bound by `All(p)` which make an atom of `p`
+/// unify with a complementary literal in `context`.
+///
+/// `context` is closed, so the terms are too.
+fn instances(context: &IdSet<Formula>, p: &Id<Formula>) -> HashSet<Id<Term>> {
+    let mut found = vec![];
+    atoms(&mut found, p, true, 0);
+    let literals = context.into_iter().filter_map(literal).collect::<Vec<_>>();
+
+    let mut instances = HashSet::new();
+    for (polarity, atom, depth) in found {
+        for (other_polarity, other) in &literals {
+            if polarity == *other_polarity {
+                continue;
+            }
+            for substitution in unify_atoms(atom, other) {
+                if let Some(t) = substitution.binding(depth) {
+                    instances.insert(t);
+                }
+            }
+        }
+    }
+    instances
 }
 
 fn complete(
-    deduced: &mut HashSet<IdSet<Formula>>,
+    deduced: &mut Deduced,
     symbols: &HashSet<(&Id<Symbol>, usize)>,
+    context: &IdSet<Formula>,
     f: &Id<Formula>,
 ) {
     match **f {
         T | F | Prd(_, _) | Eq(_) => {}
         Not(ref f) => match **f {
             T => {
-                deduced.insert(idset![Id::new(F)]);
+                deduced.insert(Rule::Negation, idset![Id::new(F)]);
             }
             F => {
-                deduced.insert(idset![Id::new(F)]);
+                deduced.insert(Rule::Negation, idset![Id::new(F)]);
             }
             Prd(_, _) => {}
             Eq(ref ts) => {
                 if ts.len() > 2 {
                     deduced.insert(
+                        Rule::AndSplit,
                         ts.pairs()
                             .map(|(t, s)| {
                                 Id::new(Formula::Not(Id::new(Eq(idset![
@@ -41,25 +115,32 @@ fn complete(
                 }
             }
             Not(ref f) => {
-                deduced.insert(idset![f.clone()]);
+                deduced.insert(Rule::Negation, idset![f.clone()]);
             }
             Imp(ref p, ref q) => {
-                deduced.insert(idset![Id::new(And(idset![
-                    p.clone(),
-                    Formula::negate(q)
-                ]))]);
+                deduced.insert(
+                    Rule::Negation,
+                    idset![Id::new(And(idset![p.clone(), Formula::negate(q)]))],
+                );
             }
             Or(ref ps) => {
-                deduced.insert(idset![Id::new(And(ps
-                    .into_iter()
-                    .map(Formula::negate)
-                    .collect()))]);
+                deduced.insert(
+                    Rule::Negation,
+                    idset![Id::new(And(ps
+                        .into_iter()
+                        .map(Formula::negate)
+                        .collect()))],
+                );
             }
             And(ref ps) => {
-                deduced.insert(ps.into_iter().map(Formula::negate).collect());
+                deduced.insert(
+                    Rule::AndSplit,
+                    ps.into_iter().map(Formula::negate).collect(),
+                );
             }
             Eqv(ref ps) => {
                 deduced.insert(
+                    Rule::EqvSplit,
                     ps.pairs()
                         .flat_map(|(p, q)| {
                             std::iter::once((p, q))
@@ -75,53 +156,63 @@ fn complete(
                 );
             }
             All(ref p) => {
-                deduced.insert(idset![Id::new(Ex(Formula::negate(p)))]);
+                deduced.insert(
+                    Rule::Negation,
+                    idset![Id::new(Ex(Formula::negate(p)))],
+                );
             }
             Ex(ref p) => {
-                deduced.insert(idset![Id::new(All(Formula::negate(p)))]);
+                deduced.insert(
+                    Rule::Negation,
+                    idset![Id::new(All(Formula::negate(p)))],
+                );
             }
         },
         Imp(ref p, ref q) => {
-            deduced.insert(idset![Formula::negate(p), q.clone()]);
+            deduced
+                .insert(Rule::AndSplit, idset![Formula::negate(p), q.clone()]);
         }
         Or(ref ps) => {
-            deduced.insert(ps.clone());
+            deduced.insert(Rule::AndSplit, ps.clone());
         }
         And(ref ps) => {
             for p in ps {
-                let mut subdeductions = HashSet::new();
-                complete(&mut subdeductions, &symbols, p);
-                for sd in subdeductions {
-                    let background: IdSet<Formula> = ps.without(p);
+                let background: IdSet<Formula> = ps.without(p);
+                let mut subdeductions = Deduced::default();
+                complete(&mut subdeductions, symbols, &background, p);
+                for (sd, weight) in subdeductions.0 {
                     let combined: IdSet<Formula> = sd
                         .into_iter()
                         .map(|f| Id::new(And(background.with(f))))
                         .collect();
-                    deduced.insert(combined);
+                    deduced.add(combined, weight);
                 }
             }
 
             let classes = ps
                 .into_iter()
                 .filter_map(|f| match **f {
-                    Eq(ref ts) => Some((f, ts.as_ref())),
+                    Eq(ref ts) => Some((f, ts)),
                     _ => None,
                 })
-                .map(|(p, class)| (p, &class[0], &class[1..]));
+                .map(|(p, class)| {
+                    let minimum = ordering::minimum(class);
+                    (p, minimum, class.without(minimum))
+                });
             for (p, minimum, class) in classes {
                 let rest = ps
                     .without(p)
                     .into_iter()
                     .map(|f| {
                         let mut f = f;
-                        for t in class {
+                        for t in &class {
                             f = Formula::replace(&f, t, minimum);
                         }
                         f
                     })
                     .chain(std::iter::once(p.clone()))
                     .collect();
-                deduced.insert(idset![Id::new(And(rest))]);
+                deduced.insert(Rule::Equality, idset![Id::new(And(rest))]);
             }
         }
         Eqv(ref ps) => {
@@ -134,33 +225,37 @@ fn complete(
                     Formula::negate(p),
                     Formula::negate(q)
                 ]));
-                deduced.insert(idset![positive, negative]);
+                deduced.insert(Rule::EqvSplit, idset![positive, negative]);
             }
         }
         All(ref p) => {
+            for t in instances(context, p) {
+                let instantiated = Formula::instantiate(p, 0, &t);
+                let combined = Id::new(And(idset![f.clone(), instantiated]));
+                deduced.insert(Rule::Unification, idset![combined]);
+            }
+            // unification finds only some instances: for completeness, also
+            // instantiate with each function symbol applied to new variables
             for (symbol, arity) in symbols {
                 let mut instantiated = Formula::subst(p, 0, symbol, *arity);
                 for _ in 0..*arity {
                     instantiated = Id::new(All(instantiated))
                 }
                 let combined = Id::new(And(idset![f.clone(), instantiated]));
-                deduced.insert(idset![combined]);
+                deduced.insert(Rule::Instantiation, idset![combined]);
             }
             let intro = introduced(p);
             let instantiated = Formula::subst(p, 0, &intro, 0);
-            deduced.insert(idset![instantiated]);
+            deduced.insert(Rule::Instantiation, idset![instantiated]);
         }
         Ex(ref p) => {
             let intro = introduced(p);
             let instantiated = Formula::subst(p, 0, &intro, 0);
-            deduced.insert(idset![instantiated]);
+            deduced.insert(Rule::Skolemisation, idset![instantiated]);
         }
     }
 }
 
-pub fn complete_deductions(
-    deduced: &mut HashSet<IdSet<Formula>>,
-    f: &Id<Formula>,
-) {
-    complete(deduced, &Formula::function_symbols(f), f);
+pub fn complete_deductions(deduced: &mut Deduced, f: &Id<Formula>) {
+    complete(deduced, &Formula::function_symbols(f), &idset![], f);
 }
@@ -1,20 +1,60 @@
+mod clause;
 mod complete;
+mod paramodulation;
+mod resolution;
+mod rules;
+mod splitting;
 mod weakening;
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 use unique::Id;
 
 use crate::collections::IdSet;
 use crate::formula::Formula;
+use crate::options::OPTIONS;
 use crate::simplification::simplify;
 
-pub fn deductions(f: &Id<Formula>) -> HashSet<IdSet<Formula>> {
-    let mut deduced = HashSet::new();
+pub use rules::{Profile, Rule, Rules};
+
+/// Inferences, each with the greatest weight of the rules deducing it
+#[derive(Default)]
+struct Deduced(HashMap<IdSet<Formula>, f32>);
+
+impl Deduced {
+    fn add(&mut self, inference: IdSet<Formula>, weight: f32) {
+        let entry = self.0.entry(inference).or_insert(weight);
+        *entry = entry.max(weight);
+    }
+
+    /// Record `inference` by `rule`, unless the rule is disabled.
+    fn insert(&mut self, rule: Rule, inference: IdSet<Formula>) {
+        let weight = OPTIONS.rules.weight(rule);
+        if weight > 0.0 {
+            self.add(inference, weight);
+        }
+    }
+}
+
+/// Inferences from `f` by the enabled rules, with their rule weights
+pub fn deductions(f: &Id<Formula>) -> HashMap<IdSet<Formula>, f32> {
+    let rules = &OPTIONS.rules;
+    let mut deduced = Deduced::default();
     complete::complete_deductions(&mut deduced, f);
+    if rules.enabled(Rule::Splitting) {
+        splitting::splitting_deductions(&mut deduced, f);
+    }
     weakening::weakening_deductions(&mut deduced, f);
+    if rules.enabled(Rule::Resolution) {
+        resolution::resolution_deductions(&mut deduced, f);
+    }
+    if rules.enabled(Rule::Paramodulation) {
+        paramodulation::paramodulation_deductions(&mut deduced, f);
+    }
 
-    deduced
-        .into_iter()
-        .map(|inference| inference.into_iter().map(|f| simplify(&f)).collect())
-        .collect()
+    let mut simplified = Deduced::default();
+    for (inference, weight) in deduced.0 {
+        let inference = inference.into_iter().map(|f| simplify(&f)).collect();
+        simplified.add(inference, weight);
+    }
+    simplified.0
 }
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+use unique::Id;
+
+use crate::cnf::close;
+use crate::deduction::clause::{add_clauses, clauses, instantiate, Clause};
+use crate::deduction::{Deduced, Rule};
+use crate::formula::Formula;
+use crate::ordering::greater;
+use crate::term::Term;
+use crate::unification::unify;
+
+/// Paramodulants from equations in `from` into a renamed copy of `into`
+fn paramodulants(from: &Clause, into: &Clause) -> Vec<Id<Formula>> {
+    let into = from.rename_apart(into);
+    let literals = from
+        .literals
+        .iter()
+        .chain(into.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut paramodulants = vec![];
+    for (i, l) in from.literals.iter().enumerate() {
+        let equation = match **l {
+            Formula::Eq(ref ts) if ts.len() == 2 => ts.as_ref(),
+            _ => continue,
+        };
+        let orientations =
+            [(&equation[0], &equation[1]), (&equation[1], &equation[0])];
+        for &(s, t) in orientations.iter() {
+            if !s.is_function() {
+                continue;
+            }
+            for (j, target) in into.iter().enumerate() {
+                let subterms = Formula::breadth_first_terms(target)
+                    .flat_map(Term::breadth_first)
+                    .filter(|u| u.is_function())
+                    .collect::<HashSet<_>>();
+                for u in subterms {
+                    let substitution = match unify(s, u) {
+                        Some(substitution) => substitution,
+                        None => continue,
+                    };
+                    let lhs = substitution.apply(s);
+                    let rhs = substitution.apply(t);
+                    if lhs == rhs || greater(&rhs, &lhs) {
+                        continue;
+                    }
+
+                    let target = &instantiate(
+                        &substitution,
+                        std::slice::from_ref(target),
+                        &[],
+                    )[0];
+                    let except = [i, from.literals.len() + j];
+                    let mut paramodulant =
+                        instantiate(&substitution, &literals, &except);
+                    paramodulant.push(Formula::replace(target, &lhs, &rhs));
+                    paramodulants.extend(close(paramodulant));
+                }
+            }
+        }
+    }
+    paramodulants
+}
+
+pub fn paramodulation_deductions(deduced: &mut Deduced, f: &Id<Formula>) {
+    let clauses = clauses(f);
+    let mut new = HashSet::new();
+    for from in &clauses {
+        for into in &clauses {
+            new.extend(paramodulants(from, into));
+        }
+    }
+    add_clauses(deduced, Rule::Paramodulation, f, new);
+}
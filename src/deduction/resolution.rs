@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use unique::Id;
+
+use crate::cnf::close;
+use crate::deduction::clause::{
+    add_clauses, atom, clauses, instantiate, Clause,
+};
+use crate::deduction::{Deduced, Rule};
+use crate::formula::Formula;
+use crate::unification::unify_atoms;
+
+/// Binary resolvents of `left` and a copy of `right` renamed apart
+fn resolvents(left: &Clause, right: &Clause) -> Vec<Id<Formula>> {
+    let right = left.rename_apart(right);
+    let literals = left
+        .literals
+        .iter()
+        .chain(right.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut resolvents = vec![];
+    for (i, l) in left.literals.iter().enumerate() {
+        let (polarity, p) = atom(l).unwrap();
+        for (j, r) in right.iter().enumerate() {
+            let (other_polarity, q) = atom(r).unwrap();
+            if polarity == other_polarity {
+                continue;
+            }
+            for substitution in unify_atoms(p, q) {
+                let except = [i, left.literals.len() + j];
+                let resolvent = instantiate(&substitution, &literals, &except);
+                resolvents.extend(close(resolvent));
+            }
+        }
+    }
+    resolvents
+}
+
+/// Factors of `clause`, unifying two of its literals
+fn factors(clause: &Clause) -> Vec<Id<Formula>> {
+    let mut factors = vec![];
+    for (i, l) in clause.literals.iter().enumerate() {
+        let (polarity, p) = atom(l).unwrap();
+        for (j, r) in clause.literals.iter().enumerate().skip(i + 1) {
+            let (other_polarity, q) = atom(r).unwrap();
+            if polarity != other_polarity {
+                continue;
+            }
+            for substitution in unify_atoms(p, q) {
+                let factor = instantiate(&substitution, &clause.literals, &[j]);
+                factors.extend(close(factor));
+            }
+        }
+    }
+    factors
+}
+
+pub fn resolution_deductions(deduced: &mut Deduced, f: &Id<Formula>) {
+    let clauses = clauses(f);
+    let mut new = HashSet::new();
+    for (i, left) in clauses.iter().enumerate() {
+        for right in &clauses[i..] {
+            new.extend(resolvents(left, right));
+        }
+        new.extend(factors(left));
+    }
+    add_clauses(deduced, Rule::Resolution, f, new);
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::str::FromStr;
+
+/// Individually-switchable deduction rules
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Rule {
+    /// push negations inwards
+    Negation,
+    /// case split on disjunctions and negated conjunctions
+    AndSplit,
+    /// case split on equivalences
+    EqvSplit,
+    /// instantiate universals with each function symbol
+    Instantiation,
+    /// instantiate universals with terms unifying complementary literals
+    Unification,
+    /// introduce constants for existentials
+    Skolemisation,
+    /// substitute least members of equivalence classes
+    Equality,
+    /// split variable-disjoint disjunctions over a SAT-solved skeleton
+    Splitting,
+    /// resolve pairs of clauses on complementary literals
+    Resolution,
+    /// rewrite a clause with an equation from another clause
+    Paramodulation,
+    /// drop a conjunct
+    WeakenConjunct,
+    /// drop a term from an equation
+    WeakenEquation,
+    /// drop a formula from an equivalence
+    WeakenEqv,
+}
+
+const RULES: [(&str, Rule); 13] = [
+    ("negation", Rule::Negation),
+    ("and-split", Rule::AndSplit),
+    ("eqv-split", Rule::EqvSplit),
+    ("instantiation", Rule::Instantiation),
+    ("unification", Rule::Unification),
+    ("skolemisation", Rule::Skolemisation),
+    ("equality", Rule::Equality),
+    ("splitting", Rule::Splitting),
+    ("resolution", Rule::Resolution),
+    ("paramodulation", Rule::Paramodulation),
+    ("weaken-conjunct", Rule::WeakenConjunct),
+    ("weaken-equation", Rule::WeakenEquation),
+    ("weaken-eqv", Rule::WeakenEqv),
+];
+
+impl FromStr for Rule {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        RULES
+            .iter()
+            .find(|(name, _)| *name == x)
+            .map(|(_, rule)| *rule)
+            .ok_or(())
+    }
+}
+
+/// Weights for rules as `rule=weight,...`: 0 disables a rule.
+///
+/// Other weights are priors for their inferences, so only affect strategies
+/// that use priors: MCTS, in PUCT selection and in the order of widening.
+#[derive(Clone, Default)]
+pub struct Rules(pub HashMap<Rule, f32>);
+
+impl Rules {
+    /// Unlisted rules have weight 1.
+    pub fn weight(&self, rule: Rule) -> f32 {
+        self.0.get(&rule).copied().unwrap_or(1.0)
+    }
+
+    pub fn enabled(&self, rule: Rule) -> bool {
+        self.weight(rule) > 0.0
+    }
+
+    /// Whether running out of inferences shows satisfiability.
+    ///
+    /// Without one of these rules, some formulas have no inferences at all,
+    /// or too few: unification alone misses instances some refutations need.
+    pub fn complete(&self) -> bool {
+        use Rule::*;
+        [
+            Negation,
+            AndSplit,
+            EqvSplit,
+            Instantiation,
+            Skolemisation,
+            Equality,
+        ]
+        .iter()
+        .all(|rule| self.enabled(*rule))
+    }
+}
+
+impl FromStr for Rules {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        let mut weights = HashMap::new();
+        let assignments = x.split(&[',', '\n'][..]);
+        for assignment in assignments.filter(|x| !x.trim().is_empty()) {
+            let mut parts = assignment.splitn(2, '=');
+            let rule = parts.next().ok_or(())?.trim().parse()?;
+            let weight = parts.next().ok_or(())?.trim();
+            let weight = weight.parse::<f32>().map_err(|_| ())?;
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(());
+            }
+            weights.insert(rule, weight);
+        }
+        Ok(Rules(weights))
+    }
+}
+
+/// Named rule sets, or a file of `rule=weight` lines
+pub enum Profile {
+    /// every rule, equally weighted
+    Default,
+    /// decomposition and weakening only
+    Tableau,
+    /// every rule, preferring clausal inferences
+    Saturation,
+    File(Rules),
+}
+
+impl Profile {
+    pub fn rules(&self) -> Rules {
+        use Rule::*;
+        let weights: &[(Rule, f32)] = match self {
+            Profile::Default => &[],
+            Profile::Tableau => {
+                &[(Splitting, 0.0), (Resolution, 0.0), (Paramodulation, 0.0)]
+            }
+            Profile::Saturation => {
+                &[(Splitting, 2.0), (Resolution, 2.0), (Paramodulation, 2.0)]
+            }
+            Profile::File(rules) => return rules.clone(),
+        };
+        Rules(weights.iter().copied().collect())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        use Profile::*;
+        match x {
+            "default" => Ok(Default),
+            "tableau" => Ok(Tableau),
+            "saturation" => Ok(Saturation),
+            path => {
+                let contents = read_to_string(path).map_err(|_| ())?;
+                Ok(File(contents.parse()?))
+            }
+        }
+    }
+}
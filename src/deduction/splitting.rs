@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::deduction::{Deduced, Rule};
+use crate::formula::Formula;
+use crate::sat::{Literal, Solver};
+
+use Formula::*;
+
+/// Splitting is abandoned if the skeleton has more branches than this
+const MAX_BRANCHES: usize = 64;
+
+/// Propositional variables naming closed components, up to negation
+#[derive(Default)]
+struct Names {
+    atoms: Vec<Id<Formula>>,
+    variables: HashMap<Id<Formula>, usize>,
+}
+
+impl Names {
+    fn split(f: &Id<Formula>) -> (bool, &Id<Formula>) {
+        match **f {
+            Not(ref p) => (false, p),
+            _ => (true, f),
+        }
+    }
+
+    fn name(&mut self, component: &Id<Formula>) -> Literal {
+        let (positive, atom) = Self::split(component);
+        let next = self.atoms.len();
+        let variable = *self.variables.entry(atom.clone()).or_insert(next);
+        if variable == next {
+            self.atoms.push(atom.clone());
+        }
+        Literal { variable, positive }
+    }
+
+    fn named(&self, component: &Id<Formula>) -> Option<Literal> {
+        let (positive, atom) = Self::split(component);
+        let variable = *self.variables.get(atom)?;
+        Some(Literal { variable, positive })
+    }
+
+    fn component(&self, literal: Literal) -> Id<Formula> {
+        let atom = &self.atoms[literal.variable];
+        if literal.positive {
+            atom.clone()
+        } else {
+            Formula::negate(atom)
+        }
+    }
+}
+
+/// A minimal set of literals true in `model` which, with `given`, satisfies
+/// each of `clauses`
+fn minimise(
+    clauses: &[Vec<Literal>],
+    given: &HashSet<Literal>,
+    model: &[bool],
+) -> Vec<Literal> {
+    let open = clauses
+        .iter()
+        .filter(|clause| !clause.iter().any(|l| given.contains(l)))
+        .collect::<Vec<_>>();
+    let mut chosen = vec![];
+    for clause in &open {
+        for l in clause.iter() {
+            if model[l.variable] == l.positive && !chosen.contains(l) {
+                chosen.push(*l);
+            }
+        }
+    }
+    for l in chosen.clone() {
+        let necessary = open.iter().any(|clause| {
+            clause.contains(&l)
+                && !clause.iter().any(|k| *k != l && chosen.contains(k))
+        });
+        if !necessary {
+            chosen.retain(|k| *k != l);
+        }
+    }
+    chosen
+}
+
+/// Split disjunctive conjuncts into their components.
+///
+/// The conjunction is refuted if it is refuted with each of a set of
+/// combinations of components. These come from the models of the
+/// propositional skeleton in which components are named, so complementary
+/// components are never chosen together. The same combination arising in
+/// several places is the same subgoal, so it is proved only once.
+pub fn splitting_deductions(deduced: &mut Deduced, f: &Id<Formula>) {
+    let ps = match **f {
+        And(ref ps) => ps,
+        _ => return,
+    };
+    let mut names = Names::default();
+    let mut clauses = vec![];
+    let mut background = vec![];
+    for p in ps {
+        match **p {
+            Or(ref qs) => clauses.push(
+                qs.into_iter().map(|q| names.name(q)).collect::<Vec<_>>(),
+            ),
+            _ => background.push(p.clone()),
+        }
+    }
+    if clauses.is_empty() {
+        return;
+    }
+    let given = background
+        .iter()
+        .filter_map(|p| names.named(p))
+        .collect::<HashSet<_>>();
+
+    let mut solver = Solver::new(names.atoms.len());
+    for clause in &clauses {
+        solver.add_clause(clause.clone());
+    }
+    for l in &given {
+        solver.add_clause(vec![*l]);
+    }
+    let mut branches = vec![];
+    while let Some(model) = solver.solve() {
+        if branches.len() == MAX_BRANCHES {
+            return;
+        }
+        let chosen = minimise(&clauses, &given, &model);
+        solver.add_clause(chosen.iter().map(|l| l.negate()).collect());
+        branches.push(chosen);
+    }
+    if branches.is_empty() {
+        deduced.insert(Rule::Splitting, idset![Id::new(F)]);
+        return;
+    }
+
+    let background = background.into_iter().collect::<IdSet<_>>();
+    deduced.insert(
+        Rule::Splitting,
+        branches
+            .into_iter()
+            .map(|chosen| {
+                let mut branch = background.clone();
+                branch.extend(chosen.into_iter().map(|l| names.component(l)));
+                Id::new(And(branch))
+            })
+            .collect(),
+    );
+}
@@ -1,49 +1,48 @@
-use std::collections::HashSet;
 use unique::Id;
 
-use crate::collections::IdSet;
+use crate::deduction::{Deduced, Rule};
 use crate::formula::Formula;
 
 use Formula::*;
 
-fn weaken(f: &Id<Formula>) -> IdSet<Formula> {
+/// Weaker formulas than `f`, with the rule giving each
+fn weaken(f: &Id<Formula>) -> Vec<(Rule, Id<Formula>)> {
     match **f {
         T | F | Prd(_, _) | Not(_) | Imp(_, _) | Or(_) | All(_) | Ex(_) => {
-            idset![]
+            vec![]
         }
         Eq(ref ts) => {
             if ts.len() > 2 {
-                ts.into_iter().map(|t| Id::new(Eq(ts.without(t)))).collect()
+                ts.into_iter()
+                    .map(|t| (Rule::WeakenEquation, Id::new(Eq(ts.without(t)))))
+                    .collect()
             } else {
-                idset![]
+                vec![]
             }
         }
         And(ref ps) => ps
             .into_iter()
-            .map(|p| Id::new(And(ps.without(p))))
+            .map(|p| (Rule::WeakenConjunct, Id::new(And(ps.without(p)))))
             .chain(ps.into_iter().flat_map(|p| {
-                weaken(p)
-                    .into_iter()
-                    .map(move |q| Id::new(And(ps.without(p).with(q))))
+                weaken(p).into_iter().map(move |(rule, q)| {
+                    (rule, Id::new(And(ps.without(p).with(q))))
+                })
             }))
             .collect(),
         Eqv(ref ps) => {
             if ps.len() > 2 {
                 ps.into_iter()
-                    .map(|p| Id::new(Eqv(ps.without(p))))
+                    .map(|p| (Rule::WeakenEqv, Id::new(Eqv(ps.without(p)))))
                     .collect()
             } else {
-                idset![]
+                vec![]
             }
         }
     }
 }
 
-pub fn weakening_deductions(
-    deduced: &mut HashSet<IdSet<Formula>>,
-    f: &Id<Formula>,
-) {
-    for f in &weaken(f) {
-        deduced.insert(idset![f.clone()]);
+pub fn weakening_deductions(deduced: &mut Deduced, f: &Id<Formula>) {
+    for (rule, f) in weaken(f) {
+        deduced.insert(rule, idset![f]);
     }
 }
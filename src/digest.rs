@@ -0,0 +1,196 @@
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::symbol::Symbol;
+use crate::term::Term;
+
+/// Cached digests: each key keeps its object alive, so its address is not
+/// re-used by another object while the entry exists.
+type Cache<T> = RwLock<HashMap<Id<T>, u64>>;
+
+/// Caches are cleared when they reach this size
+const MAX_CACHED: usize = 1 << 20;
+
+lazy_static! {
+    static ref SYMBOLS: Cache<Symbol> = RwLock::new(HashMap::new());
+    static ref TERMS: Cache<Term> = RwLock::new(HashMap::new());
+    static ref FORMULAE: Cache<Formula> = RwLock::new(HashMap::new());
+}
+
+/// FNV-1a: unlike the standard library's hasher, stable between builds.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Content-based hashes of interned objects, which do not vary between runs.
+pub trait Digest: Sized + 'static {
+    fn cache() -> &'static Cache<Self>;
+    fn compute<H: Hasher>(&self, hasher: &mut H);
+
+    fn digest(id: &Id<Self>) -> u64 {
+        if let Some(digest) = Self::cache().read().get(id) {
+            return *digest;
+        }
+        let mut hasher = Fnv::default();
+        id.compute(&mut hasher);
+        let digest = hasher.finish();
+        let mut cache = Self::cache().write();
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        cache.insert(id.clone(), digest);
+        digest
+    }
+}
+
+fn digest_all<'a, T, H, I>(hasher: &mut H, ids: I)
+where
+    T: 'a + Digest,
+    H: Hasher,
+    I: IntoIterator<Item = &'a Id<T>>,
+{
+    for id in ids {
+        hasher.write_u64(T::digest(id));
+    }
+}
+
+impl Digest for Symbol {
+    fn cache() -> &'static Cache<Self> {
+        &SYMBOLS
+    }
+
+    fn compute<H: Hasher>(&self, hasher: &mut H) {
+        use Symbol::*;
+        match self {
+            Original(s) => {
+                hasher.write_u8(0);
+                hasher.write(s.as_bytes());
+            }
+            Introduced(n) => {
+                hasher.write_u8(1);
+                hasher.write_u64(*n as u64);
+            }
+        }
+    }
+}
+
+impl Digest for Term {
+    fn cache() -> &'static Cache<Self> {
+        &TERMS
+    }
+
+    fn compute<H: Hasher>(&self, hasher: &mut H) {
+        use Term::*;
+        match self {
+            Var(n) => {
+                hasher.write_u8(0);
+                hasher.write_u64(*n as u64);
+            }
+            Fn(f, ts) => {
+                hasher.write_u8(1);
+                hasher.write_u64(Symbol::digest(f));
+                digest_all(hasher, ts);
+            }
+        }
+    }
+}
+
+impl Digest for Formula {
+    fn cache() -> &'static Cache<Self> {
+        &FORMULAE
+    }
+
+    fn compute<H: Hasher>(&self, hasher: &mut H) {
+        use Formula::*;
+        match self {
+            T => hasher.write_u8(0),
+            F => hasher.write_u8(1),
+            Eq(ts) => {
+                hasher.write_u8(2);
+                digest_all(hasher, ts);
+            }
+            Prd(p, ts) => {
+                hasher.write_u8(3);
+                hasher.write_u64(Symbol::digest(p));
+                digest_all(hasher, ts);
+            }
+            Not(p) => {
+                hasher.write_u8(4);
+                hasher.write_u64(Formula::digest(p));
+            }
+            Imp(p, q) => {
+                hasher.write_u8(5);
+                hasher.write_u64(Formula::digest(p));
+                hasher.write_u64(Formula::digest(q));
+            }
+            Or(ps) => {
+                hasher.write_u8(6);
+                digest_all(hasher, ps);
+            }
+            And(ps) => {
+                hasher.write_u8(7);
+                digest_all(hasher, ps);
+            }
+            Eqv(ps) => {
+                hasher.write_u8(8);
+                digest_all(hasher, ps);
+            }
+            All(p) => {
+                hasher.write_u8(9);
+                hasher.write_u64(Formula::digest(p));
+            }
+            Ex(p) => {
+                hasher.write_u8(10);
+                hasher.write_u64(Formula::digest(p));
+            }
+        }
+    }
+}
+
+/// An identifier for `id`: its digest in deterministic mode, otherwise its
+/// (cheaper, but run-dependent) address.
+pub fn identify<T: Digest>(id: &Id<T>) -> usize {
+    if OPTIONS.deterministic {
+        T::digest(id) as usize
+    } else {
+        Id::id(id)
+    }
+}
+
+/// A run-independent name for `id`, for symbols introduced on its behalf.
+///
+/// Addresses are re-used once interned objects are freed, so they would let
+/// different formulae share an introduced symbol.
+pub fn name<T: Digest>(id: &Id<T>) -> usize {
+    T::digest(id) as usize
+}
+
+/// Forget cached digests: must be called before interned objects are freed,
+/// as the cache keeps them alive.
+pub fn forget() {
+    SYMBOLS.write().clear();
+    TERMS.write().clear();
+    FORMULAE.write().clear();
+}
@@ -0,0 +1,214 @@
+use std::io;
+use std::io::{BufRead, Write};
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::oracle::consult;
+use crate::output::tptp;
+use crate::score::Score;
+use crate::search::Search;
+use crate::status::Status;
+
+const HELP: &str = "\
+commands:
+  show              print the current formula
+  list              list inferences from the current formula
+  expand [I]        expand the current formula, or each formula of inference I
+  enter I [J]       move to formula J (default 0) of inference I
+  up                move back to the previous formula
+  root              move back to the root
+  oracle            consult the oracle on the current formula
+  score S           set the score of the current formula to S
+  state             print the state of the proof
+  help              print this message
+  quit              leave the explorer";
+
+struct Explorer {
+    search: Search,
+    path: Vec<Id<Formula>>,
+}
+
+fn write_formula(f: &Id<Formula>) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    tptp::write_formula(&mut stdout, f)?;
+    writeln!(stdout)
+}
+
+fn parse<T: std::str::FromStr>(arg: Option<&str>) -> Result<T, String> {
+    let arg = arg.ok_or("missing argument")?;
+    arg.parse().map_err(|_| format!("bad argument: {}", arg))
+}
+
+impl Explorer {
+    fn current(&self) -> &Id<Formula> {
+        self.path.last().expect("empty path")
+    }
+
+    fn inference(&self, index: usize) -> Result<Vec<Id<Formula>>, String> {
+        let inferences = self
+            .search
+            .inferences(self.current())
+            .ok_or("not expanded yet: try `expand`")?;
+        let inference = inferences
+            .get(index)
+            .ok_or_else(|| format!("no inference {}", index))?;
+        Ok(inference.into_iter().cloned().collect())
+    }
+
+    fn describe(&self, f: &Id<Formula>) -> String {
+        format!(
+            "{:?} score={:?} visits={}",
+            self.search.node_status(f),
+            self.search.node_score(f),
+            self.search.node_visits(f)
+        )
+    }
+
+    fn show(&self) -> io::Result<()> {
+        let current = self.current();
+        println!("depth {}, {}", self.path.len() - 1, self.describe(current));
+        write_formula(current)
+    }
+
+    fn list(&self) -> Result<(), String> {
+        let inferences = self
+            .search
+            .inferences(self.current())
+            .ok_or("not expanded yet: try `expand`")?;
+        for (i, inference) in inferences.iter().enumerate() {
+            println!("inference {}:", i);
+            for (j, f) in inference.into_iter().enumerate() {
+                println!("  {}: {}", j, self.describe(f));
+                print!("     ");
+                write_formula(f).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn expand(&mut self, inference: Option<&str>) -> Result<(), String> {
+        let targets = match inference {
+            Some(_) => self.inference(parse(inference)?)?,
+            None => vec![self.current().clone()],
+        };
+        // expansion may evict, which must not remove the current path
+        self.search.pin(self.path.clone());
+        for f in &targets {
+            let new_formulae = self.search.expand_now(f);
+            println!(
+                "{} inference(s), {} new formula(e)",
+                self.search.inferences(f).map_or(0, Vec::len),
+                new_formulae.len()
+            );
+        }
+        Ok(())
+    }
+
+    fn enter(
+        &mut self,
+        inference: Option<&str>,
+        formula: Option<&str>,
+    ) -> Result<(), String> {
+        let inference = self.inference(parse(inference)?)?;
+        let index = formula.map_or(Ok(0), |_| parse(formula))?;
+        let f = inference
+            .get(index)
+            .ok_or_else(|| format!("no formula {}", index))?
+            .clone();
+        self.path.push(f);
+        self.show().map_err(|e| e.to_string())
+    }
+
+    fn oracle(&mut self) {
+        let current = self.current().clone();
+        let status = consult(&current);
+        println!("oracle says {:?}", status);
+        self.search.set_status(&current, status);
+    }
+
+    fn score(&mut self, score: Option<&str>) -> Result<(), String> {
+        let score: f32 = parse(score)?;
+        if !score.is_finite() {
+            return Err("score should be finite".into());
+        }
+        let current = self.current().clone();
+        self.search.set_score(&current, Score::from(score));
+        Ok(())
+    }
+
+    fn state(&self) -> io::Result<()> {
+        let status = self.search.status();
+        println!(
+            "root is {:?}, {} node(s) in the search",
+            status,
+            self.search.nodes.len()
+        );
+        if status == Status::Unsat {
+            println!("proof:");
+            for f in self.search.proof() {
+                write_formula(&f)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn command(&mut self, line: &str) -> Result<bool, String> {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Ok(true),
+        };
+        let (first, second) = (words.next(), words.next());
+        let io_error = |e: io::Error| e.to_string();
+
+        match command {
+            "show" => self.show().map_err(io_error)?,
+            "list" => self.list()?,
+            "expand" => self.expand(first)?,
+            "enter" => self.enter(first, second)?,
+            "up" => {
+                if self.path.len() > 1 {
+                    self.path.pop();
+                }
+                self.show().map_err(io_error)?
+            }
+            "root" => {
+                self.path.truncate(1);
+                self.show().map_err(io_error)?
+            }
+            "oracle" => self.oracle(),
+            "score" => self.score(first)?,
+            "state" => self.state().map_err(io_error)?,
+            "help" => println!("{}", HELP),
+            "quit" | "exit" => return Ok(false),
+            _ => return Err(format!("unknown command: {}", command)),
+        }
+        Ok(true)
+    }
+}
+
+pub fn run(simplified: Id<Formula>) -> io::Result<()> {
+    let mut explorer = Explorer {
+        search: Search::new(simplified.clone()),
+        path: vec![simplified],
+    };
+    println!("type `help` for a list of commands");
+    explorer.show()?;
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        match explorer.command(&line) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+}
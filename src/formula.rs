@@ -72,6 +72,46 @@ impl Formula {
         }
     }
 
+    /// Replace variable `index` by the closed term `with`.
+    pub fn instantiate(
+        f: &Id<Formula>,
+        index: usize,
+        with: &Id<Term>,
+    ) -> Id<Formula> {
+        match **f {
+            T | F => f.clone(),
+            Eq(ref ts) => Id::new(Eq(ts
+                .into_iter()
+                .map(|t| Term::instantiate(t, index, with))
+                .collect())),
+            Prd(ref p, ref ts) => Id::new(Prd(
+                p.clone(),
+                ts.into_iter()
+                    .map(|t| Term::instantiate(t, index, with))
+                    .collect(),
+            )),
+            Not(ref p) => Id::new(Not(Self::instantiate(p, index, with))),
+            Imp(ref p, ref q) => Id::new(Imp(
+                Self::instantiate(p, index, with),
+                Self::instantiate(q, index, with),
+            )),
+            Or(ref ps) => Id::new(Or(ps
+                .into_iter()
+                .map(|p| Self::instantiate(p, index, with))
+                .collect())),
+            And(ref ps) => Id::new(And(ps
+                .into_iter()
+                .map(|p| Self::instantiate(p, index, with))
+                .collect())),
+            Eqv(ref ps) => Id::new(Eqv(ps
+                .into_iter()
+                .map(|p| Self::instantiate(p, index, with))
+                .collect())),
+            All(ref p) => Id::new(All(Self::instantiate(p, index + 1, with))),
+            Ex(ref p) => Id::new(Ex(Self::instantiate(p, index + 1, with))),
+        }
+    }
+
     pub fn replace(
         f: &Id<Formula>,
         from: &Id<Term>,
@@ -109,6 +149,55 @@ impl Formula {
         }
     }
 
+    /// Apply `map` to each top-level term of `f`.
+    pub fn map_terms(
+        f: &Id<Formula>,
+        map: &mut impl FnMut(&Id<Term>) -> Id<Term>,
+    ) -> Id<Formula> {
+        match **f {
+            T | F => f.clone(),
+            Eq(ref ts) => Id::new(Eq(ts.into_iter().map(&mut *map).collect())),
+            Prd(ref p, ref ts) => {
+                Id::new(Prd(p.clone(), ts.iter().map(&mut *map).collect()))
+            }
+            Not(ref p) => Id::new(Not(Self::map_terms(p, map))),
+            Imp(ref p, ref q) => {
+                let p = Self::map_terms(p, map);
+                Id::new(Imp(p, Self::map_terms(q, map)))
+            }
+            Or(ref ps) => Id::new(Or(ps
+                .into_iter()
+                .map(|p| Self::map_terms(p, map))
+                .collect())),
+            And(ref ps) => Id::new(And(ps
+                .into_iter()
+                .map(|p| Self::map_terms(p, map))
+                .collect())),
+            Eqv(ref ps) => Id::new(Eqv(ps
+                .into_iter()
+                .map(|p| Self::map_terms(p, map))
+                .collect())),
+            All(ref p) => Id::new(All(Self::map_terms(p, map))),
+            Ex(ref p) => Id::new(Ex(Self::map_terms(p, map))),
+        }
+    }
+
+    /// Whether variable `n` occurs free in `f`
+    pub fn occurs(f: &Id<Formula>, n: usize) -> bool {
+        match **f {
+            T | F => false,
+            Eq(_) | Prd(_, _) => Formula::breadth_first_terms(f)
+                .flat_map(Term::breadth_first)
+                .any(|t| **t == Term::Var(n)),
+            Not(ref p) => Self::occurs(p, n),
+            Imp(ref p, ref q) => Self::occurs(p, n) || Self::occurs(q, n),
+            Or(ref ps) | And(ref ps) | Eqv(ref ps) => {
+                ps.into_iter().any(|p| Self::occurs(p, n))
+            }
+            All(ref p) | Ex(ref p) => Self::occurs(p, n + 1),
+        }
+    }
+
     pub fn breadth_first(
         f: &Id<Formula>,
     ) -> impl Iterator<Item = &Id<Formula>> {
@@ -141,6 +230,14 @@ impl Formula {
             .flat_map(|t| Term::function_symbols(t))
             .collect()
     }
+
+    /// Number of subformulae and subterms, counting repeats
+    pub fn size(f: &Id<Formula>) -> usize {
+        let terms: usize = Self::breadth_first_terms(f)
+            .map(|t| Term::breadth_first(t).count())
+            .sum();
+        Self::breadth_first(f).count() + terms
+    }
 }
 
 impl fmt::Debug for Formula {
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::rc::Rc;
 use unique::Id;
 
+use crate::digest::identify;
 use crate::formula::Formula;
 use crate::term::Term;
 
@@ -45,7 +46,7 @@ fn from_term(t: &Id<Term>, bound: &[usize]) -> Rc<Node> {
         },
         Fn(ref c, ref ts) if ts.is_empty() => Node {
             flavour: Constant,
-            data: Some(Id::id(c)),
+            data: Some(identify(c)),
             children: vec![],
         },
         Fn(ref f, ref ts) => Node {
@@ -54,7 +55,7 @@ fn from_term(t: &Id<Term>, bound: &[usize]) -> Rc<Node> {
             children: vec![
                 Rc::new(Node {
                     flavour: FunctionSymbol,
-                    data: Some(Id::id(f)),
+                    data: Some(identify(f)),
                     children: vec![],
                 }),
                 Rc::new(Node {
@@ -92,7 +93,7 @@ fn from_formula(f: &Id<Formula>, bound: &mut Vec<usize>) -> Rc<Node> {
         },
         Prd(ref p, ref ts) if ts.is_empty() => Node {
             flavour: Proposition,
-            data: Some(Id::id(p)),
+            data: Some(identify(p)),
             children: vec![],
         },
         Prd(ref p, ref ts) => Node {
@@ -101,7 +102,7 @@ fn from_formula(f: &Id<Formula>, bound: &mut Vec<usize>) -> Rc<Node> {
             children: vec![
                 Rc::new(Node {
                     flavour: PredicateSymbol,
-                    data: Some(Id::id(p)),
+                    data: Some(identify(p)),
                     children: vec![],
                 }),
                 Rc::new(Node {
@@ -147,14 +148,14 @@ fn from_formula(f: &Id<Formula>, bound: &mut Vec<usize>) -> Rc<Node> {
             children: ps.into_iter().map(|p| from_formula(p, bound)).collect(),
         },
         All(ref p) => {
-            bound.push(Id::id(p));
+            bound.push(identify(p));
             let node = Node {
                 flavour: Universal,
                 data: None,
                 children: vec![
                     Rc::new(Node {
                         flavour: Variable,
-                        data: Some(Id::id(p)),
+                        data: Some(identify(p)),
                         children: vec![],
                     }),
                     from_formula(p, bound),
@@ -164,14 +165,14 @@ fn from_formula(f: &Id<Formula>, bound: &mut Vec<usize>) -> Rc<Node> {
             node
         }
         Ex(ref p) => {
-            bound.push(Id::id(p));
+            bound.push(identify(p));
             let node = Node {
                 flavour: Existential,
                 data: None,
                 children: vec![
                     Rc::new(Node {
                         flavour: Variable,
-                        data: Some(Id::id(p)),
+                        data: Some(identify(p)),
                         children: vec![],
                     }),
                     from_formula(p, bound),
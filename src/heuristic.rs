@@ -1,66 +1,141 @@
-use bufstream::BufStream;
 use lazy_static::lazy_static;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
 use std::collections::HashMap;
-use std::io::ErrorKind::WouldBlock;
-use std::io::{BufRead, Write};
-use std::net::TcpStream;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use unique::Id;
 
+use crate::collections::IdSet;
 use crate::formula::Formula;
 use crate::graph::flatten;
 use crate::options::OPTIONS;
 use crate::score::Score;
 use crate::system::os_error;
 
+/// Questions the search asks of the heuristic
+pub enum Query {
+    /// How promising is this formula?
+    Score(Id<Formula>),
+    /// How should the formula's inferences (in this order) be weighted?
+    Priors(Id<Formula>, Vec<IdSet<Formula>>),
+}
+
+/// Answers to a `Query`
+pub enum Advice {
+    Score(Id<Formula>, Score),
+    /// priors for the inferences that were asked about
+    Priors(Id<Formula>, HashMap<IdSet<Formula>, Score>),
+}
+
+enum Asked {
+    Score,
+    Priors(Vec<IdSet<Formula>>),
+}
+
+#[derive(Serialize)]
+struct Graph {
+    nodes: Vec<u8>,
+    edges: Vec<(usize, usize)>,
+}
+
 #[derive(Serialize)]
 struct OutRecord {
     id: usize,
     nodes: Vec<u8>,
     edges: Vec<(usize, usize)>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    inferences: Vec<Graph>,
 }
 
 #[derive(Deserialize)]
 struct InRecord {
     id: usize,
-    score: f32,
+    #[serde(default)]
+    score: Option<f32>,
+    #[serde(default)]
+    priors: Option<Vec<f32>>,
 }
 
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
 lazy_static! {
-    static ref IN_FLIGHT: RwLock<HashMap<usize, Id<Formula>>> =
+    static ref IN_FLIGHT: RwLock<HashMap<usize, (Id<Formula>, Asked)>> =
         RwLock::new(HashMap::new());
-    static ref SOCKET: Mutex<BufStream<TcpStream>> = {
+    static ref SOCKET: TcpStream = {
         let socket = TcpStream::connect(&OPTIONS.heuristic_address)
             .unwrap_or_else(|e| {
                 log::error!("could not connect to heuristic: {}", e);
                 os_error()
             });
-        socket.set_nonblocking(true).unwrap_or_else(|e| {
-            log::error!("setting non-blocking socket options failed: {}", e);
-            os_error()
-        });
         log::info!("connected to heuristic at {}", OPTIONS.heuristic_address);
-        Mutex::new(BufStream::new(socket))
+        socket
     };
+    static ref READER: Mutex<BufReader<TcpStream>> =
+        Mutex::new(BufReader::new(clone_socket()));
+    static ref WRITER: Mutex<BufWriter<TcpStream>> =
+        Mutex::new(BufWriter::new(clone_socket()));
+}
+
+static CONNECTED: AtomicBool = AtomicBool::new(true);
+
+fn clone_socket() -> TcpStream {
+    SOCKET.try_clone().unwrap_or_else(|e| {
+        log::error!("failed to clone heuristic socket: {}", e);
+        os_error()
+    })
+}
+
+fn connected() -> bool {
+    CONNECTED.load(Ordering::SeqCst)
+}
+
+fn graph(f: &Id<Formula>) -> Graph {
+    let (nodes, edges) = flatten(f.into());
+    Graph { nodes, edges }
 }
 
-pub fn send_to_heuristic(f: &Id<Formula>) -> bool {
-    let id = Id::id(f);
+/// Blocks until `query` is written: false if the heuristic was disconnected
+pub fn send_to_heuristic(query: &Query) -> bool {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (f, asked, inferences) = match query {
+        Query::Score(f) => (f, Asked::Score, vec![]),
+        Query::Priors(f, inferences) => (
+            f,
+            Asked::Priors(inferences.clone()),
+            inferences
+                .iter()
+                .map(|inference| {
+                    graph(&Id::new(Formula::And(inference.clone())))
+                })
+                .collect(),
+        ),
+    };
     {
         let mut in_flight = IN_FLIGHT.write();
-        in_flight.insert(id, f.clone());
+        in_flight.insert(id, (f.clone(), asked));
     }
 
-    let (nodes, edges) = flatten(f.into());
-    let record = OutRecord { id, nodes, edges };
+    let Graph { nodes, edges } = graph(f);
+    let record = OutRecord {
+        id,
+        nodes,
+        edges,
+        inferences,
+    };
     let mut serialized = to_string(&record).unwrap();
     serialized.push('\n');
-    let write_result = SOCKET.lock().write_all(serialized.as_bytes());
+    let write_result = {
+        let mut writer = WRITER.lock();
+        writer
+            .write_all(serialized.as_bytes())
+            .and_then(|_| writer.flush())
+    };
     match write_result {
         Ok(_) => true,
-        Err(ref e) if e.kind() == WouldBlock => false,
+        Err(_) if !connected() => false,
         Err(e) => {
             log::error!("failed to write to heuristic socket: {}", e);
             os_error()
@@ -68,40 +143,86 @@ pub fn send_to_heuristic(f: &Id<Formula>) -> bool {
     }
 }
 
-pub fn receive_from_heuristic() -> Option<(Id<Formula>, Score)> {
-    let mut line = String::new();
-    let read_result = SOCKET.lock().read_line(&mut line);
-    match read_result {
-        Ok(_) => {}
-        Err(ref e) if e.kind() == WouldBlock => return None,
-        Err(e) => {
-            log::error!("failed to read from heuristic socket: {}", e);
-            os_error()
+fn bad_data(reason: &str) -> ! {
+    log::error!("heuristic provided bad data: {}", reason);
+    os_error()
+}
+
+/// Blocks until advice arrives: `None` if the heuristic was disconnected
+pub fn receive_from_heuristic() -> Option<Advice> {
+    loop {
+        let mut line = String::new();
+        let read_result = READER.lock().read_line(&mut line);
+        match read_result {
+            Ok(0) | Err(_) if !connected() => return None,
+            Ok(0) => {
+                log::error!("heuristic closed the connection");
+                os_error()
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("failed to read from heuristic socket: {}", e);
+                os_error()
+            }
+        };
+        if line == "\n" {
+            continue;
         }
-    };
-    if line == "\n" {
-        return None;
+        let record: InRecord =
+            from_str(&line).unwrap_or_else(|e| bad_data(&e.to_string()));
+
+        let (f, asked) = match IN_FLIGHT.write().remove(&record.id) {
+            Some(asked) => asked,
+            None => continue,
+        };
+
+        return Some(match asked {
+            Asked::Score => {
+                let score =
+                    record.score.unwrap_or_else(|| bad_data("missing score"));
+                Advice::Score(f, score.into())
+            }
+            Asked::Priors(inferences) => {
+                let priors =
+                    record.priors.unwrap_or_else(|| bad_data("missing priors"));
+                if priors.len() != inferences.len()
+                    || priors.iter().any(|p| *p < 0.0)
+                {
+                    bad_data("priors do not match inferences")
+                }
+                let priors = inferences
+                    .into_iter()
+                    .zip(priors.into_iter().map(Score::from))
+                    .collect();
+                Advice::Priors(f, priors)
+            }
+        });
     }
-    let record: InRecord = from_str(&line).unwrap_or_else(|e| {
-        log::error!("heuristic provided bad data: {}", e);
-        os_error();
-    });
-
-    let f = match IN_FLIGHT.read().get(&record.id) {
-        Some(f) => f.clone(),
-        None => {
-            return None;
-        }
-    };
-    IN_FLIGHT.write().remove(&record.id);
+}
 
-    let score = record.score.into();
-    Some((f, score))
+/// How many queries have been sent
+pub fn queries() -> usize {
+    NEXT_ID.load(Ordering::Relaxed)
+}
+
+/// How many queries are waiting for advice
+pub fn pending() -> usize {
+    IN_FLIGHT.read().len()
+}
+
+/// Wake up any blocked readers and writers, which then stop
+pub fn disconnect() {
+    CONNECTED.store(false, Ordering::SeqCst);
+    if let Err(e) = SOCKET.shutdown(Shutdown::Both) {
+        log::warn!("failed to shut down heuristic socket: {}", e);
+    }
 }
 
 pub fn initialize() {
     if !OPTIONS.heuristic_off {
         lazy_static::initialize(&IN_FLIGHT);
         lazy_static::initialize(&SOCKET);
+        lazy_static::initialize(&READER);
+        lazy_static::initialize(&WRITER);
     }
 }
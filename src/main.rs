@@ -1,7 +1,10 @@
 #[macro_use]
 mod collections;
 
+mod cnf;
 mod deduction;
+mod digest;
+mod explore;
 mod formula;
 mod graph;
 mod heuristic;
@@ -9,19 +12,24 @@ mod input;
 mod logging;
 mod options;
 mod oracle;
+mod ordering;
 mod output;
 mod prover;
 mod record;
+mod sat;
 mod score;
 mod search;
 mod simplification;
+mod stats;
 mod status;
 mod symbol;
 mod system;
 mod term;
+mod unification;
 
 use unique::Id;
 
+use crate::cnf::{clausal_form, clausify};
 use crate::formula::Formula;
 use crate::input::load;
 use crate::options::{Mode, OPTIONS};
@@ -29,7 +37,10 @@ use crate::oracle::consult;
 use crate::prover::Prover;
 use crate::simplification::simplify;
 use crate::status::Status;
-use crate::system::{check_for_timeout, satisfiable, time_out, unsatisfiable};
+use crate::system::{
+    check_for_timeout, clausified, gave_up, os_error, satisfiable, time_out,
+    unsatisfiable,
+};
 
 fn run_baseline(simplified: Id<Formula>) {
     log::info!("running oracle...");
@@ -57,6 +68,10 @@ fn run_prover(simplified: Id<Formula>) {
 
     use Status::*;
     match prover.run() {
+        Sat if !OPTIONS.rules.complete() => {
+            log::info!("...no inferences left, but some rules are disabled");
+            gave_up()
+        }
         Sat => {
             log::info!("...problem satisfiable");
             satisfiable()
@@ -73,22 +88,43 @@ fn run_prover(simplified: Id<Formula>) {
     }
 }
 
+fn run_explorer(simplified: Id<Formula>) {
+    log::info!("running explorer...");
+    explore::run(simplified).unwrap_or_else(|e| {
+        log::error!("explorer failed: {}", e);
+        os_error()
+    });
+}
+
+fn run_clausify(simplified: Id<Formula>) {
+    log::info!("clausifying...");
+    clausified(clausify(&simplified))
+}
+
 fn main() {
     system::initialize();
     options::initialize();
     logging::initialize();
-    heuristic::initialize();
-    record::initialize();
+    if !matches!(OPTIONS.mode, Mode::Clausify) {
+        heuristic::initialize();
+        record::initialize();
+    }
 
     check_for_timeout();
 
     let loaded = load();
-    let simplified = simplify(&loaded.goal);
+    ordering::initialize(&loaded.goal);
+    let mut simplified = simplify(&loaded.goal);
+    if OPTIONS.clausify {
+        simplified = clausal_form(&simplified);
+    }
     check_for_timeout();
 
     use Mode::*;
     match OPTIONS.mode {
         Baseline => run_baseline(simplified),
         Prover => run_prover(simplified),
+        Explore => run_explorer(simplified),
+        Clausify => run_clausify(simplified),
     }
 }
@@ -1,13 +1,18 @@
-use clap::{App, Arg, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use lazy_static::lazy_static;
 use std::str::FromStr;
 use std::time::Duration;
 
+use crate::deduction::{Profile, Rules};
 use crate::oracle::Oracle;
+use crate::ordering::{Precedence, TermOrdering, Weights};
+use crate::search::{Restarts, Selection, Strategy};
 
 pub enum Mode {
     Baseline,
     Prover,
+    Explore,
+    Clausify,
 }
 
 impl FromStr for Mode {
@@ -26,10 +31,26 @@ impl FromStr for Mode {
 pub struct Options {
     // global options
     pub exploration: f32,
+    pub search: Strategy,
+    pub selection: Selection,
+    pub restarts: Restarts,
+    pub restart_interval: usize,
+    pub restart_perturbation: f32,
+    pub widening: Option<f32>,
+    pub widening_exponent: f32,
+    pub lemmas: bool,
+    pub clausify: bool,
+    pub rules: Rules,
+    pub ordering: TermOrdering,
+    pub precedence: Precedence,
+    pub weights: Weights,
     pub file: String,
     pub mode: Mode,
     pub time: Duration,
     pub quiet: bool,
+    pub search_threads: u16,
+    pub memory_limit: Option<usize>,
+    pub deterministic: bool,
     // oracle options
     pub oracle: Oracle,
     pub oracle_threads: u16,
@@ -40,6 +61,14 @@ pub struct Options {
     pub heuristic_off: bool,
     // record options
     pub record_file: Option<String>,
+    // checkpoint options
+    pub checkpoint_file: Option<String>,
+    pub resume_file: Option<String>,
+    // inspection options
+    pub dump_search: Option<String>,
+    pub dump_depth: Option<usize>,
+    pub dump_visits: usize,
+    pub stats_file: Option<String>,
 }
 
 fn validate<T: FromStr>(arg: &str, error: &str) -> Result<(), String> {
@@ -60,6 +89,27 @@ impl Options {
             .version(env!("CARGO_PKG_VERSION"))
             .author(env!("CARGO_PKG_AUTHORS"))
             .about(env!("CARGO_PKG_DESCRIPTION"))
+            .setting(AppSettings::SubcommandsNegateReqs)
+            .subcommand(
+                SubCommand::with_name("explore")
+                    .about("Explore the search for PROBLEM interactively")
+                    .arg(
+                        Arg::with_name("PROBLEM")
+                            .help("the input problem")
+                            .required(true)
+                            .index(1),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("clausify")
+                    .about("Print clauses for PROBLEM in TPTP CNF")
+                    .arg(
+                        Arg::with_name("PROBLEM")
+                            .help("the input problem")
+                            .required(true)
+                            .index(1),
+                    ),
+            )
             .arg(
                 Arg::with_name("FILE")
                     .help("the input problem")
@@ -77,6 +127,151 @@ impl Options {
                     })
                     .default_value("1.4142"),
             )
+            .arg(
+                Arg::with_name("search")
+                    .help("Search strategy")
+                    .long("search")
+                    .takes_value(true)
+                    .value_name("STRATEGY")
+                    .possible_values(&["mcts", "pns", "best-first", "deepening"])
+                    .default_value("mcts"),
+            )
+            .arg(
+                Arg::with_name("selection")
+                    .help("Selection rule used to descend the search graph")
+                    .long("selection")
+                    .takes_value(true)
+                    .value_name("RULE")
+                    .possible_values(&["uct", "puct"])
+                    .default_value("uct"),
+            )
+            .arg(
+                Arg::with_name("restarts")
+                    .help("Restart schedule for MCTS: statuses are kept, visits and scores are not")
+                    .long("restarts")
+                    .takes_value(true)
+                    .value_name("SCHEDULE")
+                    .possible_values(&["none", "luby", "geometric"])
+                    .default_value("none"),
+            )
+            .arg(
+                Arg::with_name("restart interval")
+                    .help("Expansions in the shortest interval between restarts")
+                    .long("restart_interval")
+                    .takes_value(true)
+                    .value_name("EXPANSIONS")
+                    .validator(|x| {
+                        validate::<usize>(
+                            &x,
+                            "should be a positive number of expansions",
+                        )
+                    })
+                    .default_value("512"),
+            )
+            .arg(
+                Arg::with_name("restart perturbation")
+                    .help("Perturb exploration by up to this fraction on restart")
+                    .long("restart_perturbation")
+                    .takes_value(true)
+                    .value_name("FRACTION")
+                    .validator(|x| {
+                        validate::<f32>(&x, "should be a floating-point number")
+                    })
+                    .default_value("0"),
+            )
+            .arg(
+                Arg::with_name("widening")
+                    .help("Progressive widening for MCTS: C * visits^ALPHA inferences are visible")
+                    .long("widening")
+                    .takes_value(true)
+                    .value_name("C")
+                    .validator(|x| {
+                        validate::<f32>(&x, "should be a floating-point number")
+                    }),
+            )
+            .arg(
+                Arg::with_name("widening exponent")
+                    .help("Exponent ALPHA for progressive widening")
+                    .long("widening_exponent")
+                    .takes_value(true)
+                    .value_name("ALPHA")
+                    .validator(|x| {
+                        validate::<f32>(&x, "should be a floating-point number")
+                    })
+                    .default_value("0.5"),
+            )
+            .arg(
+                Arg::with_name("lemmas")
+                    .long("lemmas")
+                    .help("Share proved subgoals between branches of the search"),
+            )
+            .arg(
+                Arg::with_name("clausify")
+                    .long("clausify")
+                    .help("Convert the problem to clauses before search"),
+            )
+            .arg(
+                Arg::with_name("profile")
+                    .help("Deduction rules to use: a named profile, or a file of RULE=WEIGHT lines")
+                    .long("profile")
+                    .takes_value(true)
+                    .value_name("PROFILE")
+                    .validator(|x| {
+                        validate::<Profile>(
+                            &x,
+                            "should be default, tableau, saturation, or a readable file of rule weights",
+                        )
+                    })
+                    .default_value("default"),
+            )
+            .arg(
+                Arg::with_name("rules")
+                    .help("Rule weights overriding the profile: 0 disables a rule, others are priors for MCTS")
+                    .long("rules")
+                    .takes_value(true)
+                    .value_name("RULE=WEIGHT,...")
+                    .validator(|x| {
+                        validate::<Rules>(
+                            &x,
+                            "should be rule=weight pairs with non-negative weights, separated by commas",
+                        )
+                    }),
+            )
+            .arg(
+                Arg::with_name("ordering")
+                    .help("Term ordering for equational reasoning")
+                    .long("ordering")
+                    .takes_value(true)
+                    .value_name("ORDERING")
+                    .possible_values(&["kbo", "lpo"])
+                    .default_value("kbo"),
+            )
+            .arg(
+                Arg::with_name("precedence")
+                    .help("Symbols in increasing precedence, above all others, separated by commas")
+                    .long("precedence")
+                    .takes_value(true)
+                    .value_name("SYMBOLS")
+                    .validator(|x| {
+                        validate::<Precedence>(
+                            &x,
+                            "should be symbol names separated by commas",
+                        )
+                    }),
+            )
+            .arg(
+                Arg::with_name("weights")
+                    .help("KBO weights for symbols, otherwise 1")
+                    .long("weights")
+                    .takes_value(true)
+                    .value_name("SYMBOL=WEIGHT,...")
+                    .validator(|x| {
+                        validate::<Weights>(
+                            &x,
+                            "should be symbol=weight pairs with positive weights, separated by commas",
+                        )
+                    }),
+            )
             .arg(
                 Arg::with_name("mode")
                     .help("Mode of operation")
@@ -107,6 +302,38 @@ impl Options {
                     .long("quiet")
                     .help("Turns off logging, except errors"),
             )
+            .arg(
+                Arg::with_name("search threads")
+                    .help("Number of threads used for search")
+                    .long("search_threads")
+                    .takes_value(true)
+                    .value_name("THREADS")
+                    .validator(|x| {
+                        validate::<u16>(
+                            &x,
+                            "should be a positive number of threads",
+                        )
+                    })
+                    .default_value("1"),
+            )
+            .arg(
+                Arg::with_name("memory limit")
+                    .help("Memory budget: cold parts of the search are evicted")
+                    .long("memory_limit")
+                    .takes_value(true)
+                    .value_name("MEGABYTES")
+                    .validator(|x| {
+                        validate::<usize>(
+                            &x,
+                            "should be a positive number of megabytes",
+                        )
+                    }),
+            )
+            .arg(
+                Arg::with_name("deterministic")
+                    .long("deterministic")
+                    .help("Reproducible runs: one search thread, sequential oracle and heuristic"),
+            )
             .arg(
                 Arg::with_name("oracle")
                     .help("Oracle to use")
@@ -179,13 +406,114 @@ impl Options {
                     .takes_value(true)
                     .value_name("RECORD_FILE"),
             )
+            .arg(
+                Arg::with_name("checkpoint file")
+                    .help("Save the search to CHECKPOINT_FILE when stopping")
+                    .long("checkpoint_file")
+                    .takes_value(true)
+                    .value_name("CHECKPOINT_FILE"),
+            )
+            .arg(
+                Arg::with_name("resume file")
+                    .help("Resume the search saved in RESUME_FILE")
+                    .long("resume_file")
+                    .takes_value(true)
+                    .value_name("RESUME_FILE"),
+            )
+            .arg(
+                Arg::with_name("dump search")
+                    .help("Write the search graph to DOT_FILE when stopping")
+                    .long("dump_search")
+                    .takes_value(true)
+                    .value_name("DOT_FILE"),
+            )
+            .arg(
+                Arg::with_name("dump depth")
+                    .help("Only dump formulae within DEPTH inferences of the root")
+                    .long("dump_depth")
+                    .takes_value(true)
+                    .value_name("DEPTH")
+                    .validator(|x| {
+                        validate::<usize>(&x, "should be a positive depth")
+                    }),
+            )
+            .arg(
+                Arg::with_name("dump visits")
+                    .help("Only dump formulae visited at least VISITS times")
+                    .long("dump_visits")
+                    .takes_value(true)
+                    .value_name("VISITS")
+                    .validator(|x| {
+                        validate::<usize>(
+                            &x,
+                            "should be a positive number of visits",
+                        )
+                    })
+                    .default_value("0"),
+            )
+            .arg(
+                Arg::with_name("stats file")
+                    .help("Write statistics for the run to FILE, as JSON")
+                    .long("stats")
+                    .takes_value(true)
+                    .value_name("FILE"),
+            )
             .get_matches();
 
-        let file = get_validated_arg(&matches, "FILE");
-        let mode = get_validated_arg(&matches, "mode");
-        let time = Duration::from_secs(get_validated_arg(&matches, "time"));
+        let (file, mode) = match matches.subcommand() {
+            ("explore", Some(explore)) => {
+                (get_validated_arg(explore, "PROBLEM"), Mode::Explore)
+            }
+            ("clausify", Some(clausify)) => {
+                (get_validated_arg(clausify, "PROBLEM"), Mode::Clausify)
+            }
+            _ => (
+                get_validated_arg(&matches, "FILE"),
+                get_validated_arg(&matches, "mode"),
+            ),
+        };
+        // exploration is not time-limited unless asked
+        let time = match mode {
+            Mode::Explore if matches.occurrences_of("time") == 0 => {
+                Duration::from_secs(u64::MAX)
+            }
+            _ => Duration::from_secs(get_validated_arg(&matches, "time")),
+        };
         let exploration = get_validated_arg(&matches, "exploration");
+        let search = get_validated_arg(&matches, "search");
+        let selection = get_validated_arg(&matches, "selection");
+        let restarts = get_validated_arg(&matches, "restarts");
+        let restart_interval = get_validated_arg(&matches, "restart interval");
+        let restart_perturbation =
+            get_validated_arg(&matches, "restart perturbation");
+        let widening = matches
+            .value_of("widening")
+            .map(|_| get_validated_arg(&matches, "widening"));
+        let widening_exponent =
+            get_validated_arg(&matches, "widening exponent");
+        let lemmas = matches.is_present("lemmas");
+        let clausify = matches.is_present("clausify");
+        let mut rules =
+            get_validated_arg::<Profile>(&matches, "profile").rules();
+        if matches.value_of("rules").is_some() {
+            let overrides: Rules = get_validated_arg(&matches, "rules");
+            rules.0.extend(overrides.0);
+        }
+        let ordering = get_validated_arg(&matches, "ordering");
+        let precedence = matches
+            .value_of("precedence")
+            .map(|_| get_validated_arg(&matches, "precedence"))
+            .unwrap_or_default();
+        let weights = matches
+            .value_of("weights")
+            .map(|_| get_validated_arg(&matches, "weights"))
+            .unwrap_or_default();
         let quiet = matches.is_present("quiet");
+        let search_threads = get_validated_arg(&matches, "search threads");
+        let memory_limit = matches.value_of("memory limit").map(|_| {
+            get_validated_arg::<usize>(&matches, "memory limit") << 20
+        });
+        let deterministic = matches.is_present("deterministic");
 
         let oracle = get_validated_arg(&matches, "oracle");
         let oracle_threads = get_validated_arg(&matches, "oracle threads");
@@ -199,12 +527,39 @@ impl Options {
 
         let record_file = matches.value_of("record file").map(|x| x.into());
 
+        let checkpoint_file =
+            matches.value_of("checkpoint file").map(|x| x.into());
+        let resume_file = matches.value_of("resume file").map(|x| x.into());
+
+        let dump_search = matches.value_of("dump search").map(|x| x.into());
+        let dump_depth = matches
+            .value_of("dump depth")
+            .map(|_| get_validated_arg(&matches, "dump depth"));
+        let dump_visits = get_validated_arg(&matches, "dump visits");
+        let stats_file = matches.value_of("stats file").map(|x| x.into());
+
         Options {
             exploration,
+            search,
+            selection,
+            restarts,
+            restart_interval,
+            restart_perturbation,
+            widening,
+            widening_exponent,
+            lemmas,
+            clausify,
+            rules,
+            ordering,
+            precedence,
+            weights,
             file,
             mode,
             time,
             quiet,
+            search_threads,
+            memory_limit,
+            deterministic,
             oracle,
             oracle_threads,
             oracle_iterations,
@@ -212,6 +567,12 @@ impl Options {
             heuristic_address,
             heuristic_off,
             record_file,
+            checkpoint_file,
+            resume_file,
+            dump_search,
+            dump_depth,
+            dump_visits,
+            stats_file,
         }
     }
 }
@@ -1,4 +1,5 @@
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use unique::Id;
 
 use crate::formula::Formula;
@@ -26,8 +27,16 @@ impl FromStr for Oracle {
     }
 }
 
+static CONSULTATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// How many times the oracle has been consulted
+pub fn consultations() -> usize {
+    CONSULTATIONS.load(Ordering::Relaxed)
+}
+
 pub fn consult(f: &Id<Formula>) -> Status {
     use Oracle::*;
+    CONSULTATIONS.fetch_add(1, Ordering::Relaxed);
     match OPTIONS.oracle {
         Null => null::run(f),
         Z3 => z3::run(f),
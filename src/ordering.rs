@@ -0,0 +1,234 @@
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::str::FromStr;
+use unique::Id;
+
+use crate::digest::identify;
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::symbol::Symbol;
+use crate::term::Term;
+
+use Term::*;
+
+/// Simplification orderings on terms
+pub enum TermOrdering {
+    Kbo,
+    Lpo,
+}
+
+impl FromStr for TermOrdering {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        use TermOrdering::*;
+        match x {
+            "kbo" => Ok(Kbo),
+            "lpo" => Ok(Lpo),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Symbol names in increasing precedence, separated by commas
+#[derive(Default)]
+pub struct Precedence(pub Vec<String>);
+
+impl FromStr for Precedence {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        let names = x.split(',').map(|name| name.trim().to_string());
+        let names = names.collect::<Vec<_>>();
+        if names.iter().any(String::is_empty) {
+            return Err(());
+        }
+        Ok(Precedence(names))
+    }
+}
+
+/// Positive KBO weights for symbol names, as `name=weight,...`
+#[derive(Default)]
+pub struct Weights(pub HashMap<String, usize>);
+
+impl FromStr for Weights {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        let mut weights = HashMap::new();
+        for assignment in x.split(',') {
+            let mut parts = assignment.splitn(2, '=');
+            let name = parts.next().ok_or(())?.trim();
+            let weight = parts.next().ok_or(())?.trim();
+            let weight = weight.parse::<usize>().map_err(|_| ())?;
+            if name.is_empty() || weight == 0 {
+                return Err(());
+            }
+            weights.insert(name.to_string(), weight);
+        }
+        Ok(Weights(weights))
+    }
+}
+
+/// Precedence ranks and weights for symbols of the input
+#[derive(Default)]
+struct Signature {
+    ranks: HashMap<Id<Symbol>, usize>,
+    weights: HashMap<Id<Symbol>, usize>,
+}
+
+lazy_static! {
+    static ref SIGNATURE: RwLock<Signature> = RwLock::new(Signature::default());
+}
+
+impl Signature {
+    /// Symbols not in the input, such as introduced symbols, are least.
+    fn rank(&self, f: &Id<Symbol>) -> (usize, usize) {
+        self.ranks
+            .get(f)
+            .map_or((0, identify(f)), |rank| (1, *rank))
+    }
+
+    fn weight(&self, t: &Id<Term>) -> usize {
+        match **t {
+            Var(_) => 1,
+            Fn(ref f, ref ts) => {
+                let own = self.weights.get(f).copied().unwrap_or(1);
+                own + ts.iter().map(|t| self.weight(t)).sum::<usize>()
+            }
+        }
+    }
+
+    fn kbo(&self, s: &Id<Term>, t: &Id<Term>) -> bool {
+        if !variables_include(s, t) {
+            return false;
+        }
+        match self.weight(s).cmp(&self.weight(t)) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match (&**s, &**t) {
+                (Fn(f, ss), Fn(g, ts)) => match self.rank(f).cmp(&self.rank(g))
+                {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => {
+                        let first =
+                            ss.iter().zip(ts.iter()).find(|(s, t)| s != t);
+                        matches!(first, Some((s, t)) if self.kbo(s, t))
+                    }
+                },
+                _ => false,
+            },
+        }
+    }
+
+    fn lpo(&self, s: &Id<Term>, t: &Id<Term>) -> bool {
+        match (&**s, &**t) {
+            (Var(_), _) => false,
+            (Fn(_, _), Var(_)) => {
+                s != t && Term::breadth_first(s).any(|u| u == t)
+            }
+            (Fn(f, ss), Fn(g, ts)) => {
+                if ss.iter().any(|s| s == t || self.lpo(s, t)) {
+                    return true;
+                }
+                let dominates = || ts.iter().all(|t| self.lpo(s, t));
+                match self.rank(f).cmp(&self.rank(g)) {
+                    Ordering::Greater => dominates(),
+                    Ordering::Less => false,
+                    Ordering::Equal => {
+                        let first =
+                            ss.iter().zip(ts.iter()).find(|(s, t)| s != t);
+                        matches!(first, Some((s, t)) if self.lpo(s, t))
+                            && dominates()
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn variable_counts(t: &Id<Term>) -> HashMap<usize, usize> {
+    let mut counts = HashMap::new();
+    for t in Term::breadth_first(t) {
+        if let Var(n) = **t {
+            *counts.entry(n).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Whether each variable occurs in `s` at least as often as in `t`
+fn variables_include(s: &Id<Term>, t: &Id<Term>) -> bool {
+    let s_counts = variable_counts(s);
+    variable_counts(t)
+        .into_iter()
+        .all(|(n, count)| matches!(s_counts.get(&n), Some(s) if *s >= count))
+}
+
+/// Whether `s` is greater than `t` in the chosen ordering
+pub fn greater(s: &Id<Term>, t: &Id<Term>) -> bool {
+    let signature = SIGNATURE.read();
+    match OPTIONS.ordering {
+        TermOrdering::Kbo => signature.kbo(s, t),
+        TermOrdering::Lpo => signature.lpo(s, t),
+    }
+}
+
+/// A least element of `terms`, which must not be empty
+pub fn minimum<'a, I: IntoIterator<Item = &'a Id<Term>>>(
+    terms: I,
+) -> &'a Id<Term> {
+    let mut terms = terms.into_iter();
+    let first = terms.next().expect("no terms");
+    terms.fold(first, |least, t| if greater(least, t) { t } else { least })
+}
+
+/// Set up the precedence and weights for the symbols of `problem`.
+///
+/// By default, symbols of higher arity take precedence, then rarer ones.
+/// Symbols listed in the `precedence` option follow all others.
+pub fn initialize(problem: &Id<Formula>) {
+    let mut frequencies: HashMap<(&Id<Symbol>, usize), usize> = HashMap::new();
+    for t in Formula::breadth_first_terms(problem) {
+        for t in Term::breadth_first(t) {
+            if let Fn(ref f, ref ts) = **t {
+                *frequencies.entry((f, ts.len())).or_default() += 1;
+            }
+        }
+    }
+
+    let listed = &OPTIONS.precedence.0;
+    let name = |f: &Id<Symbol>| match **f {
+        Symbol::Original(ref name) => name.clone(),
+        Symbol::Introduced(_) => String::new(),
+    };
+    let mut symbols = frequencies
+        .into_iter()
+        .filter(|((f, _), _)| !listed.contains(&name(f)))
+        .map(|((f, arity), frequency)| (arity, Reverse(frequency), name(f), f))
+        .collect::<Vec<_>>();
+    symbols.sort_by(|x, y| (x.0, x.1, &x.2).cmp(&(y.0, y.1, &y.2)));
+    let order = symbols.into_iter().map(|(_, _, _, f)| f.clone()).chain(
+        listed
+            .iter()
+            .map(|name| Id::new(Symbol::Original(name.clone()))),
+    );
+
+    let mut signature = SIGNATURE.write();
+    signature.ranks.clear();
+    for f in order {
+        let rank = signature.ranks.len();
+        signature.ranks.entry(f).or_insert(rank);
+    }
+    signature.weights = OPTIONS
+        .weights
+        .0
+        .iter()
+        .map(|(name, weight)| {
+            (Id::new(Symbol::Original(name.clone())), *weight)
+        })
+        .collect();
+}
@@ -4,6 +4,7 @@ use std::io::Write;
 use tptp::syntax::*;
 use unique::Id;
 
+use crate::digest::identify;
 use crate::formula::Formula;
 use crate::symbol::Symbol;
 use crate::term::Term;
@@ -94,13 +95,67 @@ fn formula(f: &Id<Formula>, bound: usize) -> FofFormula {
 }
 
 fn statement_name(f: &Id<Formula>) -> Name {
-    Name::LowerWord(Cow::Owned(format!("f{:x}", Id::id(f))))
+    Name::LowerWord(Cow::Owned(format!("f{:x}", identify(f))))
 }
 
 fn statement(f: &Id<Formula>) -> Statement {
     Statement::Fof(statement_name(f), FormulaRole::Plain, formula(f, 0), None)
 }
 
+fn literal(l: &Id<Formula>, bound: usize) -> CnfLiteral<'_> {
+    use Formula::*;
+    let (positive, atom) = match **l {
+        Not(ref p) => (false, p),
+        _ => (true, l),
+    };
+    match **atom {
+        Eq(ref ts) if ts.len() == 2 => {
+            let ts = ts.as_ref();
+            let equality = if positive {
+                InfixEquality::Equal
+            } else {
+                InfixEquality::NotEqual
+            };
+            CnfLiteral::Literal(FofFormula::Infix(
+                equality,
+                term(&ts[0], bound),
+                term(&ts[1], bound),
+            ))
+        }
+        _ if positive => CnfLiteral::Literal(formula(atom, bound)),
+        _ => CnfLiteral::NegatedLiteral(formula(atom, bound)),
+    }
+}
+
+/// `f` is a universally-closed disjunction of literals
+fn clause(f: &Id<Formula>) -> CnfFormula<'_> {
+    let mut bound = 0;
+    let mut body = f;
+    while let Formula::All(ref p) = **body {
+        bound += 1;
+        body = p;
+    }
+    let literals = match **body {
+        Formula::F => vec![CnfLiteral::Literal(FofFormula::Boolean(false))],
+        Formula::Or(ref ps) => {
+            ps.into_iter().map(|p| literal(p, bound)).collect()
+        }
+        _ => vec![literal(body, bound)],
+    };
+    CnfFormula(literals)
+}
+
+pub fn write_clause<W: Write>(w: &mut W, f: &Id<Formula>) -> io::Result<()> {
+    let clause = clause(f);
+    let statement =
+        Statement::Cnf(statement_name(f), FormulaRole::Plain, clause, None);
+    writeln!(w, "{}", statement)
+}
+
 pub fn write_statement<W: Write>(w: &mut W, f: &Id<Formula>) -> io::Result<()> {
     writeln!(w, "{}", statement(f))
 }
+
+pub fn write_formula<W: Write>(w: &mut W, f: &Id<Formula>) -> io::Result<()> {
+    write!(w, "{}", formula(f, 0))
+}
@@ -1,20 +1,30 @@
-use crossbeam::channel::{bounded, unbounded, Receiver, Sender};
+use crossbeam::channel::{bounded, select, unbounded, Receiver, Sender};
 use crossbeam::thread;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread::yield_now;
+use parking_lot::{Condvar, Mutex};
+use std::fs::File;
+use std::io::BufWriter;
 use unique::Id;
 
+use crate::deduction::deductions;
 use crate::formula::Formula;
-use crate::heuristic::{receive_from_heuristic, send_to_heuristic};
+use crate::heuristic;
+use crate::heuristic::{
+    disconnect, receive_from_heuristic, send_to_heuristic, Advice, Query,
+};
 use crate::options::OPTIONS;
 use crate::oracle::consult;
-use crate::score::Score;
-use crate::search::Search;
+use crate::search::{Search, Selection};
+use crate::stats::{Progress, Queues, REPORT_INTERVAL};
 use crate::status::Status;
-use crate::system::{os_error, within_time};
+use crate::system::{input_error, os_error, remaining_time, within_time};
 
 const MAX_QUEUED: usize = 128;
 
+struct Shared<'a> {
+    search: Mutex<&'a mut Search>,
+    progress: Condvar,
+}
+
 pub struct Prover {
     pub problem: Id<Formula>,
     pub search: Search,
@@ -22,126 +32,365 @@ pub struct Prover {
 
 impl Prover {
     pub fn new(problem: Id<Formula>) -> Self {
-        let search = Search::new(problem.clone());
+        let search = match OPTIONS.resume_file {
+            Some(ref path) => resume(path, &problem),
+            None => Search::new(problem.clone()),
+        };
         Self { problem, search }
     }
 
     pub fn run(&mut self) -> Status {
+        let mut progress = if OPTIONS.deterministic {
+            self.run_sequential()
+        } else {
+            self.run_concurrent()
+        };
+        progress.report(&self.search, Queues::default());
+
+        if let Some(ref path) = OPTIONS.checkpoint_file {
+            log::info!("saving search to '{}'...", path);
+            self.search.save(path).unwrap_or_else(|e| {
+                log::error!("failed to save search: {}", e);
+                os_error()
+            });
+            log::info!("...saved {} node(s)", self.search.nodes.len());
+        }
+
+        if let Some(ref path) = OPTIONS.dump_search {
+            log::info!("writing search graph to '{}'...", path);
+            File::create(path)
+                .and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    self.search.write_dot(
+                        &mut writer,
+                        OPTIONS.dump_depth,
+                        OPTIONS.dump_visits,
+                    )
+                })
+                .unwrap_or_else(|e| {
+                    log::error!("failed to write search graph: {}", e);
+                    os_error()
+                });
+        }
+
+        if let Some(ref path) = OPTIONS.stats_file {
+            progress
+                .write_statistics(path, &self.search)
+                .unwrap_or_else(|e| {
+                    log::error!("failed to write statistics: {}", e);
+                    os_error()
+                });
+        }
+
+        self.search.status()
+    }
+
+    /// Search in one thread, consulting the oracle and heuristic in turn:
+    /// the same problem and options then give the same search.
+    fn run_sequential(&mut self) -> Progress {
+        let mut progress = Progress::default();
+        let search = &mut self.search;
+        ask_heuristic(search, Query::Score(self.problem.clone()));
+        let status = consult(&self.problem);
+        search.set_status(&self.problem, status);
+
+        while !search.status().is_known() && within_time() {
+            let leaf = match search.begin_step() {
+                Some(leaf) => leaf,
+                None => break,
+            };
+            let deduced = deductions(&leaf);
+            let new_formulae = search.finish_step(&leaf, deduced);
+            for f in &new_formulae {
+                let status = consult(f);
+                search.set_status(f, status);
+            }
+            for f in new_formulae {
+                ask_heuristic(search, Query::Score(f));
+            }
+            if let Some(query) = priors_query(search, &leaf) {
+                ask_heuristic(search, query);
+            }
+            if progress.due() {
+                progress.report(search, Queues::default());
+            }
+        }
+
+        if !OPTIONS.heuristic_off {
+            disconnect();
+        }
+        progress
+    }
+
+    fn run_concurrent(&mut self) -> Progress {
         let (search2heuristic_send, search2heuristic_receive) =
             bounded(MAX_QUEUED);
         let (search2oracle_send, search2oracle_receive) = bounded(MAX_QUEUED);
         let (heuristic2search_send, heuristic2search_receive) = unbounded();
         let (oracle2search_send, oracle2search_receive) = unbounded();
 
-        search2heuristic_send.send(self.problem.clone()).unwrap();
+        // nothing is ever sent: dropping the sender wakes up all workers
+        let (shutdown_send, shutdown_receive) = bounded::<()>(0);
+
+        if !OPTIONS.heuristic_off {
+            search2heuristic_send
+                .send(Query::Score(self.problem.clone()))
+                .unwrap();
+        }
         search2oracle_send.send(self.problem.clone()).unwrap();
 
-        let running = AtomicBool::new(true);
-        thread::scope(|s| {
+        let shared = Shared {
+            search: Mutex::new(&mut self.search),
+            progress: Condvar::new(),
+        };
+        let progress = thread::scope(|s| {
             for _ in 0..OPTIONS.oracle_threads {
-                s.spawn(|_| {
-                    oracle_task(
-                        &running,
-                        search2oracle_receive.clone(),
-                        oracle2search_send.clone(),
-                    )
-                });
+                let shutdown = shutdown_receive.clone();
+                let oracle_in = search2oracle_receive.clone();
+                let oracle_out = oracle2search_send.clone();
+                s.spawn(move |_| oracle_task(shutdown, oracle_in, oracle_out));
             }
 
             if !OPTIONS.heuristic_off {
-                s.spawn(|_| {
-                    heuristic_in_task(&running, search2heuristic_receive)
+                let shutdown = shutdown_receive.clone();
+                s.spawn(move |_| {
+                    heuristic_in_task(shutdown, search2heuristic_receive)
+                });
+                s.spawn(|_| heuristic_out_task(&heuristic2search_send));
+            }
+
+            {
+                let shared = &shared;
+                let shutdown = shutdown_receive.clone();
+                let oracle_recv = oracle2search_receive.clone();
+                let heuristic_recv = heuristic2search_receive.clone();
+                s.spawn(move |_| {
+                    results_task(shared, shutdown, oracle_recv, heuristic_recv)
                 });
-                s.spawn(|_| {
-                    heuristic_out_task(&running, heuristic2search_send)
+            }
+
+            let reporter = {
+                let shared = &shared;
+                let shutdown = shutdown_receive.clone();
+                let oracle_queue = search2oracle_send.clone();
+                let heuristic_queue = search2heuristic_send.clone();
+                s.spawn(move |_| {
+                    progress_task(
+                        shared,
+                        shutdown,
+                        oracle_queue,
+                        heuristic_queue,
+                    )
+                })
+            };
+
+            let searchers = (0..OPTIONS.search_threads.max(1))
+                .map(|_| {
+                    let shared = &shared;
+                    let heuristic_send = search2heuristic_send.clone();
+                    let oracle_send = search2oracle_send.clone();
+                    s.spawn(move |_| {
+                        search_task(shared, heuristic_send, oracle_send)
+                    })
+                })
+                .collect::<Vec<_>>();
+            for searcher in searchers {
+                searcher.join().unwrap_or_else(|e| {
+                    log::error!("search thread panicked: {:?}", e);
+                    os_error()
                 });
             }
 
-            let result = search_task(
-                &mut self.search,
-                search2heuristic_send,
-                search2oracle_send,
-                heuristic2search_receive,
-                oracle2search_receive,
-            );
-            running.store(false, Ordering::Relaxed);
-            result
+            drop(shutdown_send);
+            if !OPTIONS.heuristic_off {
+                disconnect();
+            }
+            reporter.join().unwrap_or_else(|e| {
+                log::error!("progress thread panicked: {:?}", e);
+                os_error()
+            })
         })
         .unwrap_or_else(|e| {
             log::error!("failed to run worker threads: {:?}", e);
             os_error()
-        })
+        });
+        progress
     }
 }
 
+fn resume(path: &str, problem: &Id<Formula>) -> Search {
+    log::info!("resuming search from '{}'...", path);
+    let search = Search::load(path).unwrap_or_else(|e| {
+        log::error!("failed to load saved search: {}", e);
+        os_error()
+    });
+    if search.root() != problem {
+        log::error!("saved search is for a different problem");
+        input_error()
+    }
+    log::info!("...resumed {} node(s)", search.nodes.len());
+    search
+}
+
 fn oracle_task(
-    running: &AtomicBool,
+    shutdown: Receiver<()>,
     oracle_in: Receiver<Id<Formula>>,
     oracle_out: Sender<(Id<Formula>, Status)>,
 ) {
-    while running.load(Ordering::Relaxed) {
-        if let Ok(f) = oracle_in.try_recv() {
-            let consultation = consult(&f);
-            if oracle_out.send((f, consultation)).is_err() {
-                return;
+    loop {
+        select! {
+            recv(oracle_in) -> f => {
+                let f = match f {
+                    Ok(f) => f,
+                    Err(_) => return,
+                };
+                let consultation = consult(&f);
+                if oracle_out.send((f, consultation)).is_err() {
+                    return;
+                }
             }
-        } else {
+            recv(shutdown) -> _ => return,
         }
     }
 }
 
-fn heuristic_in_task(
-    running: &AtomicBool,
-    heuristic_in: Receiver<Id<Formula>>,
-) {
-    while running.load(Ordering::Relaxed) {
-        if let Ok(f) = heuristic_in.try_recv() {
-            while !send_to_heuristic(&f) {
-                yield_now()
+fn heuristic_in_task(shutdown: Receiver<()>, heuristic_in: Receiver<Query>) {
+    loop {
+        select! {
+            recv(heuristic_in) -> query => {
+                let query = match query {
+                    Ok(query) => query,
+                    Err(_) => return,
+                };
+                if !send_to_heuristic(&query) {
+                    return;
+                }
             }
-        } else {
-            yield_now()
+            recv(shutdown) -> _ => return,
         }
     }
 }
 
-fn heuristic_out_task(
-    running: &AtomicBool,
-    heuristic_out: Sender<(Id<Formula>, Score)>,
-) {
-    while running.load(Ordering::Relaxed) {
-        if let Some(scored) = receive_from_heuristic() {
-            if heuristic_out.send(scored).is_err() {
-                return;
+fn heuristic_out_task(heuristic_out: &Sender<Advice>) {
+    while let Some(advice) = receive_from_heuristic() {
+        if heuristic_out.send(advice).is_err() {
+            return;
+        }
+    }
+}
+
+fn apply_advice(search: &mut Search, advice: Advice) {
+    match advice {
+        Advice::Score(f, score) => search.set_score(&f, score),
+        Advice::Priors(f, priors) => search.set_priors(&f, priors),
+    }
+}
+
+/// Ask the heuristic for advice and wait for the answer.
+fn ask_heuristic(search: &mut Search, query: Query) {
+    if OPTIONS.heuristic_off || !send_to_heuristic(&query) {
+        return;
+    }
+    if let Some(advice) = receive_from_heuristic() {
+        apply_advice(search, advice);
+    }
+}
+
+/// A request for priors over the inferences from a newly-expanded `leaf`.
+fn priors_query(search: &Search, leaf: &Id<Formula>) -> Option<Query> {
+    match OPTIONS.selection {
+        Selection::Puct if !OPTIONS.heuristic_off => search
+            .inferences(leaf)
+            .filter(|inferences| !inferences.is_empty())
+            .map(|inferences| Query::Priors(leaf.clone(), inferences.clone())),
+        _ => None,
+    }
+}
+
+fn progress_task(
+    shared: &Shared,
+    shutdown: Receiver<()>,
+    oracle_queue: Sender<Id<Formula>>,
+    heuristic_queue: Sender<Query>,
+) -> Progress {
+    let mut progress = Progress::default();
+    loop {
+        select! {
+            recv(shutdown) -> _ => return progress,
+            default(REPORT_INTERVAL) => {
+                let queues = Queues {
+                    oracle: oracle_queue.len(),
+                    heuristic: heuristic_queue.len() + heuristic::pending(),
+                };
+                progress.report(&shared.search.lock(), queues);
             }
-        } else {
-            yield_now();
         }
     }
 }
 
-pub fn search_task(
-    search: &mut Search,
-    heuristic_send: Sender<Id<Formula>>,
-    oracle_send: Sender<Id<Formula>>,
-    heuristic_recv: Receiver<(Id<Formula>, Score)>,
+/// Apply oracle results and heuristic advice as they arrive, waking searchers.
+fn results_task(
+    shared: &Shared,
+    shutdown: Receiver<()>,
     oracle_recv: Receiver<(Id<Formula>, Status)>,
-) -> Status {
-    while !search.status().is_known() && within_time() {
-        if let Ok((f, status)) = oracle_recv.try_recv() {
-            search.set_status(&f, status);
-        } else if let Ok((f, score)) = heuristic_recv.try_recv() {
-            search.set_score(&f, score);
-        } else {
-            let new_formulae = search.do_step();
-            for f in new_formulae {
-                oracle_send.send(f.clone()).unwrap();
-                if !OPTIONS.heuristic_off {
-                    heuristic_send.send(f).unwrap();
-                }
+    heuristic_recv: Receiver<Advice>,
+) {
+    loop {
+        select! {
+            recv(oracle_recv) -> result => {
+                let (f, status) = match result {
+                    Ok(result) => result,
+                    Err(_) => return,
+                };
+                shared.search.lock().set_status(&f, status);
             }
+            recv(heuristic_recv) -> result => {
+                let advice = match result {
+                    Ok(advice) => advice,
+                    Err(_) => return,
+                };
+                apply_advice(&mut shared.search.lock(), advice);
+            }
+            recv(shutdown) -> _ => return,
         }
+        shared.progress.notify_all();
     }
+}
 
-    search.status()
+fn search_task(
+    shared: &Shared,
+    heuristic_send: Sender<Query>,
+    oracle_send: Sender<Id<Formula>>,
+) {
+    while within_time() {
+        let mut search = shared.search.lock();
+        let leaf = match search.begin_step() {
+            Some(leaf) => leaf,
+            // another thread may have settled the root since
+            None if search.status().is_known() => break,
+            // wait for a step, result or advice to change the graph
+            None => {
+                shared.progress.wait_for(&mut search, remaining_time());
+                continue;
+            }
+        };
+        drop(search);
+
+        let deduced = deductions(&leaf);
+        let mut search = shared.search.lock();
+        let new_formulae = search.finish_step(&leaf, deduced);
+        let priors_query = priors_query(&search, &leaf);
+        drop(search);
+        shared.progress.notify_all();
+
+        for f in new_formulae {
+            oracle_send.send(f.clone()).unwrap();
+            if !OPTIONS.heuristic_off {
+                heuristic_send.send(Query::Score(f)).unwrap();
+            }
+        }
+        if let Some(query) = priors_query {
+            heuristic_send.send(query).unwrap();
+        }
+    }
 }
@@ -0,0 +1,115 @@
+/// A propositional literal: a variable and its polarity
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Literal {
+    pub variable: usize,
+    pub positive: bool,
+}
+
+impl Literal {
+    pub fn negate(self) -> Self {
+        Literal {
+            variable: self.variable,
+            positive: !self.positive,
+        }
+    }
+
+    fn value(self, assignment: &[Option<bool>]) -> Option<bool> {
+        assignment[self.variable].map(|value| value == self.positive)
+    }
+}
+
+/// Clauses over the variables `0..variables`, solved by DPLL
+pub struct Solver {
+    variables: usize,
+    clauses: Vec<Vec<Literal>>,
+}
+
+impl Solver {
+    pub fn new(variables: usize) -> Self {
+        Solver {
+            variables,
+            clauses: vec![],
+        }
+    }
+
+    pub fn add_clause(&mut self, clause: Vec<Literal>) {
+        self.clauses.push(clause);
+    }
+
+    /// A satisfying assignment, if there is one
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut assignment = vec![None; self.variables];
+        if self.search(&mut assignment) {
+            Some(assignment.into_iter().map(|x| x.unwrap_or(false)).collect())
+        } else {
+            None
+        }
+    }
+
+    /// Assign literals of unit clauses until none remain.
+    ///
+    /// `false` if some clause is falsified.
+    fn propagate(&self, assignment: &mut [Option<bool>]) -> bool {
+        loop {
+            let mut changed = false;
+            for clause in &self.clauses {
+                let mut unassigned = None;
+                let mut count = 0;
+                let mut satisfied = false;
+                for literal in clause {
+                    match literal.value(assignment) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            unassigned = Some(*literal);
+                            count += 1;
+                        }
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match (count, unassigned) {
+                    (0, _) => return false,
+                    (1, Some(literal)) => {
+                        assignment[literal.variable] = Some(literal.positive);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    fn search(&self, assignment: &mut Vec<Option<bool>>) -> bool {
+        if !self.propagate(assignment) {
+            return false;
+        }
+        let open = self.clauses.iter().find_map(|clause| {
+            if clause.iter().any(|l| l.value(assignment) == Some(true)) {
+                None
+            } else {
+                clause.iter().find(|l| l.value(assignment).is_none())
+            }
+        });
+        let literal = match open {
+            Some(literal) => *literal,
+            None => return true,
+        };
+        for decision in &[literal, literal.negate()] {
+            let mut branch = assignment.clone();
+            branch[decision.variable] = Some(decision.positive);
+            if self.search(&mut branch) {
+                *assignment = branch;
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,87 @@
+use std::cmp::{Ordering, Reverse};
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::score::Score;
+use crate::search::{Search, Strategy};
+
+/// An unexpanded leaf, as it was scored when queued
+pub struct Candidate {
+    score: Score,
+    sequence: Reverse<usize>,
+    formula: Id<Formula>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.score, self.sequence).cmp(&(other.score, other.sequence))
+    }
+}
+
+impl Search {
+    /// Queue `f` for best-first search, if it is an unexpanded leaf.
+    ///
+    /// Stale entries are left in the queue and skipped by `best_leaf`.
+    pub(super) fn push_frontier(&mut self, f: &Id<Formula>) {
+        match OPTIONS.search {
+            Strategy::BestFirst => {}
+            _ => return,
+        }
+
+        let node = self.node(f);
+        if node.children.is_none() && !node.status.is_known() {
+            let candidate = Candidate {
+                score: node.score,
+                sequence: Reverse(self.queued),
+                formula: f.clone(),
+            };
+            self.frontier.push(candidate);
+            self.queued += 1;
+        }
+    }
+
+    /// The best-scoring leaf which may still contribute to a proof.
+    ///
+    /// Ties are broken in favour of leaves queued first.
+    pub(super) fn best_leaf(&mut self) -> Option<Id<Formula>> {
+        while let Some(candidate) = self.frontier.pop() {
+            let f = candidate.formula;
+            let node = self.node(&f);
+            let stale = node.score != candidate.score
+                || node.children.is_some()
+                || node.status.is_known()
+                || self.in_flight.contains_key(&f);
+            let relevant = f == self.root
+                || node
+                    .parents
+                    .iter()
+                    .any(|parent| !self.node_status(parent).is_known());
+            if !stale && relevant {
+                return Some(f);
+            }
+        }
+        None
+    }
+
+    /// Forget queued leaves which are no longer in the graph.
+    pub(super) fn prune_frontier(&mut self) {
+        let nodes = &self.nodes;
+        self.frontier
+            .retain(|candidate| nodes.contains_key(&candidate.formula));
+    }
+}
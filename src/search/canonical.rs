@@ -0,0 +1,240 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::symbol::Symbol;
+use crate::term::Term;
+
+/// Hashes of term and formula structure which ignore the names of
+/// introduced symbols, so are the same for alpha-equivalent formulae.
+#[derive(Default)]
+struct Shapes {
+    terms: HashMap<Id<Term>, u64>,
+    formulae: HashMap<Id<Formula>, u64>,
+}
+
+impl Shapes {
+    fn symbol<H: Hasher>(s: &Id<Symbol>, hasher: &mut H) {
+        use Symbol::*;
+        match **s {
+            Original(ref name) => {
+                hasher.write_u8(0);
+                name.hash(hasher);
+            }
+            Introduced(_) => hasher.write_u8(1),
+        }
+    }
+
+    fn term(&mut self, t: &Id<Term>) -> u64 {
+        use Term::*;
+        if let Some(shape) = self.terms.get(t) {
+            return *shape;
+        }
+        let mut hasher = DefaultHasher::new();
+        match **t {
+            Var(n) => {
+                hasher.write_u8(0);
+                hasher.write_usize(n);
+            }
+            Fn(ref f, ref ts) => {
+                hasher.write_u8(1);
+                Self::symbol(f, &mut hasher);
+                for t in ts {
+                    hasher.write_u64(self.term(t));
+                }
+            }
+        }
+        let shape = hasher.finish();
+        self.terms.insert(t.clone(), shape);
+        shape
+    }
+
+    fn formula(&mut self, f: &Id<Formula>) -> u64 {
+        use Formula::*;
+        if let Some(shape) = self.formulae.get(f) {
+            return *shape;
+        }
+        let mut hasher = DefaultHasher::new();
+        match **f {
+            T => hasher.write_u8(0),
+            F => hasher.write_u8(1),
+            Eq(ref ts) => {
+                hasher.write_u8(2);
+                let mut shapes =
+                    ts.into_iter().map(|t| self.term(t)).collect::<Vec<_>>();
+                shapes.sort_unstable();
+                shapes.hash(&mut hasher);
+            }
+            Prd(ref p, ref ts) => {
+                hasher.write_u8(3);
+                Self::symbol(p, &mut hasher);
+                for t in ts {
+                    hasher.write_u64(self.term(t));
+                }
+            }
+            Not(ref p) => {
+                hasher.write_u8(4);
+                hasher.write_u64(self.formula(p));
+            }
+            Imp(ref p, ref q) => {
+                hasher.write_u8(5);
+                hasher.write_u64(self.formula(p));
+                hasher.write_u64(self.formula(q));
+            }
+            Or(ref ps) | And(ref ps) | Eqv(ref ps) => {
+                hasher.write_u8(match **f {
+                    Or(_) => 6,
+                    And(_) => 7,
+                    _ => 8,
+                });
+                let mut shapes =
+                    ps.into_iter().map(|p| self.formula(p)).collect::<Vec<_>>();
+                shapes.sort_unstable();
+                shapes.hash(&mut hasher);
+            }
+            All(ref p) => {
+                hasher.write_u8(9);
+                hasher.write_u64(self.formula(p));
+            }
+            Ex(ref p) => {
+                hasher.write_u8(10);
+                hasher.write_u64(self.formula(p));
+            }
+        }
+        let shape = hasher.finish();
+        self.formulae.insert(f.clone(), shape);
+        shape
+    }
+}
+
+/// A consistent renaming of introduced symbols.
+#[derive(Default)]
+struct Renaming {
+    shapes: Shapes,
+    names: HashMap<Id<Symbol>, Id<Symbol>>,
+}
+
+impl Renaming {
+    fn name_symbol(&mut self, s: &Id<Symbol>) {
+        if let Symbol::Introduced(_) = **s {
+            if !self.names.contains_key(s) {
+                let name = Id::new(Symbol::Introduced(self.names.len()));
+                self.names.insert(s.clone(), name);
+            }
+        }
+    }
+
+    fn name_term(&mut self, t: &Id<Term>) {
+        if let Term::Fn(ref f, ref ts) = **t {
+            self.name_symbol(f);
+            for t in ts {
+                self.name_term(t);
+            }
+        }
+    }
+
+    /// Name introduced symbols in order of first occurrence, visiting
+    /// unordered operands in order of shape.
+    fn name_formula(&mut self, f: &Id<Formula>) {
+        use Formula::*;
+        match **f {
+            T | F => {}
+            Eq(ref ts) => {
+                let mut ts = ts
+                    .into_iter()
+                    .map(|t| (self.shapes.term(t), t))
+                    .collect::<Vec<_>>();
+                ts.sort_by_key(|(shape, _)| *shape);
+                for (_, t) in ts {
+                    self.name_term(t);
+                }
+            }
+            Prd(ref p, ref ts) => {
+                self.name_symbol(p);
+                for t in ts {
+                    self.name_term(t);
+                }
+            }
+            Not(ref p) | All(ref p) | Ex(ref p) => self.name_formula(p),
+            Imp(ref p, ref q) => {
+                self.name_formula(p);
+                self.name_formula(q);
+            }
+            Or(ref ps) | And(ref ps) | Eqv(ref ps) => {
+                let mut ps = ps
+                    .into_iter()
+                    .map(|p| (self.shapes.formula(p), p))
+                    .collect::<Vec<_>>();
+                ps.sort_by_key(|(shape, _)| *shape);
+                for (_, p) in ps {
+                    self.name_formula(p);
+                }
+            }
+        }
+    }
+
+    fn rename_term(&self, t: &Id<Term>) -> Id<Term> {
+        use Term::*;
+        match **t {
+            Var(_) => t.clone(),
+            Fn(ref f, ref ts) => Id::new(Fn(
+                self.names.get(f).unwrap_or(f).clone(),
+                ts.iter().map(|t| self.rename_term(t)).collect(),
+            )),
+        }
+    }
+
+    fn rename_formula(&self, f: &Id<Formula>) -> Id<Formula> {
+        use Formula::*;
+        match **f {
+            T | F => f.clone(),
+            Eq(ref ts) => Id::new(Eq(ts
+                .into_iter()
+                .map(|t| self.rename_term(t))
+                .collect())),
+            Prd(ref p, ref ts) => Id::new(Prd(
+                self.names.get(p).unwrap_or(p).clone(),
+                ts.iter().map(|t| self.rename_term(t)).collect(),
+            )),
+            Not(ref p) => Id::new(Not(self.rename_formula(p))),
+            Imp(ref p, ref q) => {
+                Id::new(Imp(self.rename_formula(p), self.rename_formula(q)))
+            }
+            Or(ref ps) => Id::new(Or(ps
+                .into_iter()
+                .map(|p| self.rename_formula(p))
+                .collect())),
+            And(ref ps) => Id::new(And(ps
+                .into_iter()
+                .map(|p| self.rename_formula(p))
+                .collect())),
+            Eqv(ref ps) => Id::new(Eqv(ps
+                .into_iter()
+                .map(|p| self.rename_formula(p))
+                .collect())),
+            All(ref p) => Id::new(All(self.rename_formula(p))),
+            Ex(ref p) => Id::new(Ex(self.rename_formula(p))),
+        }
+    }
+}
+
+/// Rename the introduced function and predicate symbols of `f` canonically,
+/// so that formulae differing only in those names usually become identical.
+///
+/// Operands with the same shape are visited in an arbitrary order, so
+/// some highly-symmetric formulae may still have several canonical forms.
+pub(super) fn canonical(f: &Id<Formula>) -> Id<Formula> {
+    let introduced = Formula::function_symbols(f)
+        .into_iter()
+        .chain(Formula::predicate_symbols(f))
+        .any(|(s, _)| matches!(**s, Symbol::Introduced(_)));
+    if !introduced {
+        return f.clone();
+    }
+
+    let mut renaming = Renaming::default();
+    renaming.name_formula(f);
+    renaming.rename_formula(f)
+}
@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::digest::identify;
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::search::{Node, Search};
+use crate::status::Status;
+use crate::symbol::Symbol;
+use crate::term::Term;
+
+/// Incremented when checkpoints of earlier versions cannot be resumed soundly
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+enum SymbolData {
+    Original(String),
+    /// named by the digest of the formula it was introduced for, which is
+    /// the same in every run: see `digest::name`
+    Introduced(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+enum TermData {
+    Var(usize),
+    Fn(usize, Vec<usize>),
+}
+
+#[derive(Serialize, Deserialize)]
+enum FormulaData {
+    T,
+    F,
+    Eq(Vec<usize>),
+    Prd(usize, Vec<usize>),
+    Not(usize),
+    Imp(usize, usize),
+    Or(Vec<usize>),
+    And(Vec<usize>),
+    Eqv(Vec<usize>),
+    All(usize),
+    Ex(usize),
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeData {
+    formula: usize,
+    parents: Vec<usize>,
+    children: Option<Vec<Vec<usize>>>,
+    priors: Option<Vec<f32>>,
+    score: f32,
+    status: Status,
+    visits: usize,
+    proof: usize,
+    disproof: usize,
+    refuted: usize,
+}
+
+/// The search graph, with interned objects flattened into tables.
+///
+/// Tables are in dependency order, so they can be re-interned front to back.
+#[derive(Default, Serialize, Deserialize)]
+struct Checkpoint {
+    /// missing before introduced symbols were named by digest
+    #[serde(default)]
+    version: u32,
+    symbols: Vec<SymbolData>,
+    terms: Vec<TermData>,
+    formulae: Vec<FormulaData>,
+    root: usize,
+    nodes: Vec<NodeData>,
+}
+
+#[derive(Default)]
+struct Encoder {
+    symbols: HashMap<Id<Symbol>, usize>,
+    terms: HashMap<Id<Term>, usize>,
+    formulae: HashMap<Id<Formula>, usize>,
+    checkpoint: Checkpoint,
+}
+
+impl Encoder {
+    fn symbol(&mut self, s: &Id<Symbol>) -> usize {
+        if let Some(index) = self.symbols.get(s) {
+            return *index;
+        }
+
+        use Symbol::*;
+        let data = match **s {
+            Original(ref name) => SymbolData::Original(name.clone()),
+            Introduced(n) => SymbolData::Introduced(n),
+        };
+        let index = self.checkpoint.symbols.len();
+        self.checkpoint.symbols.push(data);
+        self.symbols.insert(s.clone(), index);
+        index
+    }
+
+    fn term(&mut self, t: &Id<Term>) -> usize {
+        if let Some(index) = self.terms.get(t) {
+            return *index;
+        }
+
+        use Term::*;
+        let data = match **t {
+            Var(n) => TermData::Var(n),
+            Fn(ref f, ref ts) => TermData::Fn(self.symbol(f), self.terms(ts)),
+        };
+        let index = self.checkpoint.terms.len();
+        self.checkpoint.terms.push(data);
+        self.terms.insert(t.clone(), index);
+        index
+    }
+
+    fn terms<'a, I: IntoIterator<Item = &'a Id<Term>>>(
+        &mut self,
+        ts: I,
+    ) -> Vec<usize> {
+        ts.into_iter().map(|t| self.term(t)).collect()
+    }
+
+    fn formula(&mut self, f: &Id<Formula>) -> usize {
+        if let Some(index) = self.formulae.get(f) {
+            return *index;
+        }
+
+        use Formula::*;
+        let data = match **f {
+            T => FormulaData::T,
+            F => FormulaData::F,
+            Eq(ref ts) => FormulaData::Eq(self.terms(ts)),
+            Prd(ref p, ref ts) => {
+                FormulaData::Prd(self.symbol(p), self.terms(ts))
+            }
+            Not(ref p) => FormulaData::Not(self.formula(p)),
+            Imp(ref p, ref q) => {
+                FormulaData::Imp(self.formula(p), self.formula(q))
+            }
+            Or(ref ps) => FormulaData::Or(self.formulae(ps)),
+            And(ref ps) => FormulaData::And(self.formulae(ps)),
+            Eqv(ref ps) => FormulaData::Eqv(self.formulae(ps)),
+            All(ref p) => FormulaData::All(self.formula(p)),
+            Ex(ref p) => FormulaData::Ex(self.formula(p)),
+        };
+        let index = self.checkpoint.formulae.len();
+        self.checkpoint.formulae.push(data);
+        self.formulae.insert(f.clone(), index);
+        index
+    }
+
+    fn formulae(&mut self, fs: &IdSet<Formula>) -> Vec<usize> {
+        fs.into_iter().map(|f| self.formula(f)).collect()
+    }
+}
+
+#[derive(Default)]
+struct Decoder {
+    symbols: Vec<Id<Symbol>>,
+    terms: Vec<Id<Term>>,
+    formulae: Vec<Id<Formula>>,
+}
+
+impl Decoder {
+    fn get<T>(table: &[Id<T>], index: usize) -> io::Result<Id<T>> {
+        table.get(index).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "dangling reference")
+        })
+    }
+
+    fn all<T>(table: &[Id<T>], indices: &[usize]) -> io::Result<Vec<Id<T>>> {
+        indices
+            .iter()
+            .map(|index| Self::get(table, *index))
+            .collect()
+    }
+
+    fn symbol(&mut self, data: SymbolData) {
+        let symbol = match data {
+            SymbolData::Original(name) => Symbol::Original(name),
+            SymbolData::Introduced(n) => Symbol::Introduced(n),
+        };
+        self.symbols.push(Id::new(symbol));
+    }
+
+    fn term(&mut self, data: TermData) -> io::Result<()> {
+        let term = match data {
+            TermData::Var(n) => Term::Var(n),
+            TermData::Fn(f, ts) => Term::Fn(
+                Self::get(&self.symbols, f)?,
+                Self::all(&self.terms, &ts)?.into_iter().collect(),
+            ),
+        };
+        self.terms.push(Id::new(term));
+        Ok(())
+    }
+
+    fn formula(&mut self, data: FormulaData) -> io::Result<()> {
+        use Formula::*;
+        let terms = &self.terms;
+        let formulae = &self.formulae;
+        let set = |ps: &[usize]| -> io::Result<IdSet<Formula>> {
+            Ok(Self::all(formulae, ps)?.into_iter().collect())
+        };
+        let formula = match data {
+            FormulaData::T => T,
+            FormulaData::F => F,
+            FormulaData::Eq(ts) => {
+                Eq(Self::all(terms, &ts)?.into_iter().collect())
+            }
+            FormulaData::Prd(p, ts) => Prd(
+                Self::get(&self.symbols, p)?,
+                Self::all(terms, &ts)?.into_iter().collect(),
+            ),
+            FormulaData::Not(p) => Not(Self::get(formulae, p)?),
+            FormulaData::Imp(p, q) => {
+                Imp(Self::get(formulae, p)?, Self::get(formulae, q)?)
+            }
+            FormulaData::Or(ps) => Or(set(&ps)?),
+            FormulaData::And(ps) => And(set(&ps)?),
+            FormulaData::Eqv(ps) => Eqv(set(&ps)?),
+            FormulaData::All(p) => All(Self::get(formulae, p)?),
+            FormulaData::Ex(p) => Ex(Self::get(formulae, p)?),
+        };
+        self.formulae.push(Id::new(formula));
+        Ok(())
+    }
+}
+
+impl Search {
+    /// Write the whole search graph to `path`.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut encoder = Encoder::default();
+        encoder.checkpoint.version = VERSION;
+        encoder.checkpoint.root = encoder.formula(&self.root);
+        let mut entries = self.nodes.iter().collect::<Vec<_>>();
+        if OPTIONS.deterministic {
+            // resuming rebuilds the frontier in this order
+            entries.sort_by_key(|(f, _)| identify(*f));
+        }
+        for (f, node) in entries {
+            let formula = encoder.formula(f);
+            let parents = node
+                .parents
+                .iter()
+                .map(|parent| encoder.formula(parent))
+                .collect();
+            let children = node.children.as_ref().map(|children| {
+                children
+                    .iter()
+                    .map(|inference| encoder.formulae(inference))
+                    .collect()
+            });
+            let priors = node
+                .priors
+                .as_ref()
+                .map(|priors| priors.iter().map(|p| p.0).collect());
+            encoder.checkpoint.nodes.push(NodeData {
+                formula,
+                parents,
+                children,
+                priors,
+                score: node.score.0,
+                status: node.status,
+                visits: node.visits,
+                proof: node.proof,
+                disproof: node.disproof,
+                refuted: node.refuted,
+            });
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, &encoder.checkpoint)?;
+        Ok(())
+    }
+
+    /// Restore a search graph written by `save`.
+    pub fn load(path: &str) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let checkpoint: Checkpoint = serde_json::from_reader(reader)?;
+        if checkpoint.version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "checkpoint written by an incompatible version",
+            ));
+        }
+
+        let mut decoder = Decoder::default();
+        for data in checkpoint.symbols {
+            decoder.symbol(data);
+        }
+        for data in checkpoint.terms {
+            decoder.term(data)?;
+        }
+        for data in checkpoint.formulae {
+            decoder.formula(data)?;
+        }
+
+        let formulae = &decoder.formulae;
+        let root = Decoder::get(formulae, checkpoint.root)?;
+        let mut search = Search::new(root);
+        for data in checkpoint.nodes {
+            let children = match data.children {
+                Some(children) => Some(
+                    children
+                        .iter()
+                        .map(|inference| {
+                            Ok(Decoder::all(formulae, inference)?
+                                .into_iter()
+                                .collect())
+                        })
+                        .collect::<io::Result<_>>()?,
+                ),
+                None => None,
+            };
+            let node = Node {
+                parents: Decoder::all(formulae, &data.parents)?,
+                children,
+                priors: data.priors.map(|priors| {
+                    priors.into_iter().map(|p| p.into()).collect()
+                }),
+                score: data.score.into(),
+                status: data.status,
+                visits: data.visits,
+                proof: data.proof,
+                disproof: data.disproof,
+                refuted: data.refuted,
+                depth: 0,
+            };
+            search.refutations = search.refutations.max(data.refuted);
+            let f = Decoder::get(formulae, data.formula)?;
+            search.nodes.insert(f.clone(), node);
+            search.push_frontier(&f);
+        }
+        search.validate()?;
+        search.rebuild_lemmas();
+        let depths = search
+            .depths()
+            .into_iter()
+            .map(|(f, depth)| (f.clone(), depth))
+            .collect::<Vec<_>>();
+        for (f, depth) in depths {
+            search.node_mut(&f).depth = depth;
+            search.deepest = search.deepest.max(depth);
+        }
+
+        Ok(search)
+    }
+
+    /// Check that a loaded graph refers only to saved nodes.
+    fn validate(&self) -> io::Result<()> {
+        for node in self.nodes.values() {
+            let children = node.children.iter().flatten().flatten();
+            if !node
+                .parents
+                .iter()
+                .chain(children)
+                .all(|f| self.nodes.contains_key(f))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "reference to an unsaved node",
+                ));
+            }
+            let inferences = node.children.as_ref().map(Vec::len);
+            if node.priors.is_some()
+                && node.priors.as_ref().map(Vec::len) != inferences
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "priors do not match inferences",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::search::Search;
+use crate::status::Status;
+
+impl Search {
+    /// Path to the first unexpanded leaf found by a depth-first search
+    /// bounded by the current depth limit, raising the limit as necessary.
+    ///
+    /// Expanded nodes are kept, so each iteration only re-traverses them.
+    pub(super) fn deepening_path(&mut self) -> Option<Vec<Id<Formula>>> {
+        loop {
+            let mut path = vec![];
+            let mut done = HashSet::new();
+            let mut cut_off = false;
+            if self.bounded_search(
+                &self.root,
+                self.depth_limit,
+                &mut path,
+                &mut done,
+                &mut cut_off,
+            ) {
+                return Some(path);
+            }
+            if !cut_off {
+                return None;
+            }
+            self.depth_limit += 1;
+            log::debug!("deepening search to depth {}", self.depth_limit);
+        }
+    }
+
+    fn bounded_search(
+        &self,
+        f: &Id<Formula>,
+        limit: usize,
+        path: &mut Vec<Id<Formula>>,
+        done: &mut HashSet<Id<Formula>>,
+        cut_off: &mut bool,
+    ) -> bool {
+        if self.node_status(f).is_known() || !done.insert(f.clone()) {
+            return false;
+        }
+
+        path.push(f.clone());
+        let children = match self.node(f).children.as_ref() {
+            None if self.in_flight.contains_key(f) => None,
+            None => return true,
+            Some(_) if limit == 0 => {
+                *cut_off = true;
+                None
+            }
+            Some(children) => Some(children),
+        };
+
+        let possible = children.into_iter().flatten().filter(|inference| {
+            !inference
+                .into_iter()
+                .any(|f| self.node_status(f) == Status::Sat)
+        });
+        for inference in possible {
+            for f in inference {
+                if self.bounded_search(f, limit - 1, path, done, cut_off) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+}
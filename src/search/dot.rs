@@ -0,0 +1,111 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::io::Write;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::output::tptp;
+use crate::search::Search;
+use crate::status::Status;
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Search {
+    /// Write the AND/OR graph in GraphViz DOT format.
+    ///
+    /// Formulae are drawn only up to `max_depth` inferences from the root,
+    /// and if visited at least `min_visits` times.
+    /// Each inference is drawn as a small AND node joining its formulae.
+    pub fn write_dot<W: Write>(
+        &self,
+        w: &mut W,
+        max_depth: Option<usize>,
+        min_visits: usize,
+    ) -> io::Result<()> {
+        let mut shown = HashMap::new();
+        let mut todo = VecDeque::new();
+        shown.insert(self.root.clone(), 0);
+        todo.push_back((self.root.clone(), 0));
+        while let Some((f, depth)) = todo.pop_front() {
+            if matches!(max_depth, Some(max_depth) if depth >= max_depth) {
+                continue;
+            }
+            let children = self.node(&f).children.iter().flatten().flatten();
+            for child in children {
+                if !shown.contains_key(child)
+                    && self.node_visits(child) >= min_visits
+                {
+                    shown.insert(child.clone(), shown.len());
+                    todo.push_back((child.clone(), depth + 1));
+                }
+            }
+        }
+
+        writeln!(w, "digraph search {{")?;
+        writeln!(w, "\tnode [shape=box];")?;
+        let mut nodes = shown.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|(_, index)| **index);
+        for (f, index) in nodes {
+            self.write_dot_node(w, f, *index)?;
+        }
+        for (f, index) in &shown {
+            self.write_dot_inferences(w, f, *index, &shown)?;
+        }
+        writeln!(w, "}}")
+    }
+
+    fn write_dot_node<W: Write>(
+        &self,
+        w: &mut W,
+        f: &Id<Formula>,
+        index: usize,
+    ) -> io::Result<()> {
+        let node = self.node(f);
+        let mut formula = vec![];
+        tptp::write_formula(&mut formula, f)?;
+        let formula = String::from_utf8_lossy(&formula);
+        let colour = match node.status {
+            Status::Sat => "red",
+            Status::Unsat => "green",
+            Status::Unknown => "black",
+        };
+        writeln!(
+            w,
+            "\tf{} [color={}, label=\"{}\\n{:?} score={:?} visits={}\"];",
+            index,
+            colour,
+            escape(&formula),
+            node.status,
+            node.score,
+            node.visits
+        )
+    }
+
+    fn write_dot_inferences<W: Write>(
+        &self,
+        w: &mut W,
+        f: &Id<Formula>,
+        index: usize,
+        shown: &HashMap<Id<Formula>, usize>,
+    ) -> io::Result<()> {
+        let children = self.node(f).children.iter().flatten();
+        for (inference_index, inference) in children.enumerate() {
+            let drawn = inference
+                .into_iter()
+                .filter_map(|f| shown.get(f))
+                .collect::<Vec<_>>();
+            if drawn.is_empty() {
+                continue;
+            }
+
+            writeln!(w, "\ti{}_{} [shape=point];", index, inference_index)?;
+            writeln!(w, "\tf{} -> i{}_{};", index, index, inference_index)?;
+            for child in drawn {
+                writeln!(w, "\ti{}_{} -> f{};", index, inference_index, child)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+use unique::{Allocated, Allocator, Id};
+
+use crate::digest::{self, identify};
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::search::Search;
+use crate::symbol::Symbol;
+use crate::system::memory_usage;
+use crate::term::Term;
+
+/// How many expansions between checks of the memory budget
+pub(super) const CHECK_INTERVAL: usize = 256;
+
+impl Search {
+    /// Evict cold subtrees if the memory budget is exceeded.
+    ///
+    /// The node count at which the budget is first exceeded becomes a
+    /// limit on the size of the graph: memory freed by eviction is
+    /// reused by the allocator, but not necessarily returned to the OS.
+    pub(super) fn check_memory(&mut self) {
+        let budget = match OPTIONS.memory_limit {
+            Some(budget) => budget,
+            None => return,
+        };
+        if self.expansions < self.next_memory_check {
+            return;
+        }
+        self.next_memory_check = self.expansions + CHECK_INTERVAL;
+
+        if self.node_limit.is_none() {
+            let used = memory_usage().unwrap_or(0);
+            if used > budget {
+                log::info!(
+                    "memory budget exceeded with {} nodes, evicting...",
+                    self.nodes.len()
+                );
+                self.node_limit = Some(self.nodes.len());
+            }
+        }
+
+        if let Some(limit) = self.node_limit {
+            if self.nodes.len() >= limit {
+                self.evict(limit / 2);
+                release_interned();
+                log::debug!("...{} nodes after eviction", self.nodes.len());
+            }
+        }
+    }
+
+    /// Collapse the least-visited, lowest-scoring expanded nodes of unknown
+    /// status back into leaves until at most `target` nodes remain.
+    ///
+    /// Nodes of known status are kept, so their results are re-used if
+    /// they are deduced again; they are only unlinked from evicted parents.
+    fn evict(&mut self, target: usize) {
+        let mut protected: HashSet<Id<Formula>> =
+            self.in_flight.values().flatten().cloned().collect();
+        protected.insert(self.root.clone());
+        protected.extend(self.pinned.iter().cloned());
+
+        let mut candidates = self
+            .nodes
+            .iter()
+            .filter(|(f, node)| {
+                node.children.is_some()
+                    && !node.status.is_known()
+                    && !protected.contains(*f)
+            })
+            .map(|(f, node)| (node.visits, node.score, f.clone()))
+            .collect::<Vec<_>>();
+        candidates
+            .sort_by_key(|(visits, score, f)| (*visits, *score, identify(f)));
+
+        for (_, _, f) in candidates {
+            if self.nodes.len() <= target {
+                break;
+            }
+            if self.nodes.contains_key(&f) {
+                self.collapse(&f, &protected);
+                self.update_numbers(&f);
+                self.push_frontier(&f);
+            }
+        }
+
+        self.prune_frontier();
+    }
+
+    fn collapse(&mut self, f: &Id<Formula>, protected: &HashSet<Id<Formula>>) {
+        let node = self.node_mut(f);
+        node.priors = None;
+        let children = match node.children.take() {
+            Some(children) => children,
+            None => return,
+        };
+
+        for child in children.iter().flatten() {
+            let node = match self.nodes.get_mut(child) {
+                Some(node) => node,
+                None => continue,
+            };
+            node.parents.retain(|parent| parent != f);
+            if node.parents.is_empty()
+                && !node.status.is_known()
+                && !protected.contains(child)
+            {
+                self.collapse(child, protected);
+                self.nodes.remove(child);
+                self.forget_lemma(child);
+            }
+        }
+    }
+}
+
+/// Free interned objects no longer referenced anywhere.
+///
+/// Freeing a formula may release its subformulae, so sweep until stable.
+/// Digests computed concurrently keep their objects from being freed.
+fn release_interned() {
+    fn sweep<T: Allocated>() {
+        let allocator = T::allocator();
+        loop {
+            let before = allocator.allocations();
+            allocator.delete_unused();
+            if allocator.allocations() == before {
+                break;
+            }
+        }
+    }
+
+    digest::forget();
+    sweep::<Formula>();
+    sweep::<Term>();
+    sweep::<Symbol>();
+}
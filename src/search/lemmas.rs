@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::digest::identify;
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::search::Search;
+use crate::status::Status;
+
+/// The conjuncts of `f`, or just `f` if it is not a conjunction
+fn conjuncts(f: &Id<Formula>) -> IdSet<Formula> {
+    match **f {
+        Formula::And(ref ps) => ps.clone(),
+        _ => idset![f.clone()],
+    }
+}
+
+/// Index hits in a reproducible order if requested
+fn ordered(mut fs: Vec<Id<Formula>>) -> Vec<Id<Formula>> {
+    if OPTIONS.deterministic {
+        fs.sort_by_key(identify);
+    }
+    fs
+}
+
+fn is_subset(ps: &IdSet<Formula>, qs: &IdSet<Formula>) -> bool {
+    ps.len() <= qs.len() && ps.into_iter().all(|p| qs.contains(p))
+}
+
+/// Formulae indexed by their conjuncts
+#[derive(Default)]
+struct SubsetIndex {
+    postings: HashMap<Id<Formula>, HashSet<Id<Formula>>>,
+}
+
+impl SubsetIndex {
+    fn insert(&mut self, f: &Id<Formula>) {
+        for p in &conjuncts(f) {
+            self.postings
+                .entry(p.clone())
+                .or_default()
+                .insert(f.clone());
+        }
+    }
+
+    fn remove(&mut self, f: &Id<Formula>) {
+        for p in &conjuncts(f) {
+            if let Some(posting) = self.postings.get_mut(p) {
+                posting.remove(f);
+                if posting.is_empty() {
+                    self.postings.remove(p);
+                }
+            }
+        }
+    }
+
+    /// How many indexed formulae have the conjunct `p`
+    fn count(&self, p: &Id<Formula>) -> usize {
+        self.postings.get(p).map_or(0, HashSet::len)
+    }
+
+    /// Indexed formulae with every conjunct in `ps`
+    fn supersets(&self, ps: &IdSet<Formula>) -> Vec<Id<Formula>> {
+        let postings = ps
+            .into_iter()
+            .map(|p| self.postings.get(p))
+            .collect::<Option<Vec<_>>>();
+        let mut postings = match postings {
+            Some(postings) if !postings.is_empty() => postings,
+            _ => return vec![],
+        };
+        postings.sort_by_key(|posting| posting.len());
+        let (smallest, rest) = postings.split_first().unwrap();
+        ordered(
+            smallest
+                .iter()
+                .filter(|f| rest.iter().all(|posting| posting.contains(*f)))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Indexed formulae with no conjunct outside `ps`
+    fn subsets(&self, ps: &IdSet<Formula>) -> Vec<Id<Formula>> {
+        let mut hits: HashMap<&Id<Formula>, usize> = HashMap::new();
+        for p in ps {
+            for f in self.postings.get(p).into_iter().flatten() {
+                *hits.entry(f).or_default() += 1;
+            }
+        }
+        ordered(
+            hits.into_iter()
+                .filter(|(f, count)| conjuncts(f).len() == *count)
+                .map(|(f, _)| f.clone())
+                .collect(),
+        )
+    }
+}
+
+/// Proved subgoals, shared between branches of the search.
+///
+/// A conjunction including all the conjuncts of an unsatisfiable formula is
+/// unsatisfiable, and one including only conjuncts of a satisfiable formula
+/// is satisfiable.
+#[derive(Default)]
+pub(super) struct Lemmas {
+    /// nodes of unknown status
+    open: SubsetIndex,
+    /// satisfiable nodes
+    sat: SubsetIndex,
+    /// unsatisfiable nodes, filed under their least common conjunct
+    unsat: HashMap<Id<Formula>, Vec<Id<Formula>>>,
+}
+
+impl Lemmas {
+    fn unsat_subset(&self, ps: &IdSet<Formula>) -> Option<&Id<Formula>> {
+        ps.into_iter()
+            .filter_map(|p| self.unsat.get(p))
+            .flatten()
+            .find(|lemma| is_subset(&conjuncts(lemma), ps))
+    }
+}
+
+impl Search {
+    /// The status of a new node `f`, if known lemmas decide it
+    pub(super) fn lemma_status(&self, f: &Id<Formula>) -> Status {
+        if !OPTIONS.lemmas {
+            return Status::Unknown;
+        }
+        let ps = conjuncts(f);
+        if self.lemmas.unsat_subset(&ps).is_some() {
+            Status::Unsat
+        } else if !self.lemmas.sat.supersets(&ps).is_empty() {
+            Status::Sat
+        } else {
+            Status::Unknown
+        }
+    }
+
+    /// An unsatisfiable formula satisfying `usable` whose conjuncts `f`
+    /// includes
+    pub(super) fn lemma_for(
+        &self,
+        f: &Id<Formula>,
+        usable: impl Fn(&Id<Formula>) -> bool,
+    ) -> Option<&Id<Formula>> {
+        let ps = &conjuncts(f);
+        ps.into_iter()
+            .filter_map(|p| self.lemmas.unsat.get(p))
+            .flatten()
+            .find(|lemma| usable(lemma) && is_subset(&conjuncts(lemma), ps))
+    }
+
+    /// Index the new node `f`, of unknown status.
+    pub(super) fn open_lemma(&mut self, f: &Id<Formula>) {
+        if OPTIONS.lemmas {
+            self.lemmas.open.insert(f);
+        }
+    }
+
+    /// Forget the node `f`, which is being removed.
+    pub(super) fn forget_lemma(&mut self, f: &Id<Formula>) {
+        if OPTIONS.lemmas {
+            self.lemmas.open.remove(f);
+        }
+    }
+
+    /// File `f`, which has just become known, as a lemma.
+    ///
+    /// Returns open nodes which `f` now decides, with their status.
+    pub(super) fn learn_lemma(
+        &mut self,
+        f: &Id<Formula>,
+    ) -> Vec<(Id<Formula>, Status)> {
+        if !OPTIONS.lemmas {
+            return vec![];
+        }
+        self.lemmas.open.remove(f);
+        let ps = &conjuncts(f);
+        let status = self.node_status(f);
+        let decided = match status {
+            Status::Unsat => {
+                let key = ps
+                    .into_iter()
+                    .min_by_key(|p| self.lemmas.open.count(p))
+                    .cloned();
+                if let Some(key) = key {
+                    self.lemmas.unsat.entry(key).or_default().push(f.clone());
+                }
+                self.lemmas.open.supersets(ps)
+            }
+            Status::Sat => {
+                self.lemmas.sat.insert(f);
+                self.lemmas.open.subsets(ps)
+            }
+            Status::Unknown => vec![],
+        };
+        decided.into_iter().map(|g| (g, status)).collect()
+    }
+
+    /// Rebuild lemmas from the status of every node.
+    pub(super) fn rebuild_lemmas(&mut self) {
+        self.lemmas = Lemmas::default();
+        if !OPTIONS.lemmas {
+            return;
+        }
+        let nodes = self.nodes.keys().cloned().collect::<Vec<_>>();
+        for f in &nodes {
+            self.open_lemma(f);
+        }
+        for f in &nodes {
+            if self.node_status(f).is_known() {
+                self.learn_lemma(f);
+            }
+        }
+    }
+}
@@ -0,0 +1,732 @@
+mod best_first;
+mod canonical;
+mod checkpoint;
+mod deepening;
+mod dot;
+mod evict;
+mod lemmas;
+mod pns;
+mod restart;
+mod widening;
+
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::deduction::deductions;
+use crate::digest::identify;
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::record::record;
+use crate::score::Score;
+use crate::status::Status;
+use best_first::Candidate;
+use canonical::canonical;
+use lemmas::Lemmas;
+
+/// Extra visits added along a path while its leaf is being expanded,
+/// discouraging concurrent selections from following the same path
+const VIRTUAL_LOSS: usize = 3;
+
+pub enum Strategy {
+    Mcts,
+    Pns,
+    BestFirst,
+    Deepening,
+}
+
+impl FromStr for Strategy {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        use Strategy::*;
+        match x {
+            "mcts" => Ok(Mcts),
+            "pns" => Ok(Pns),
+            "best-first" => Ok(BestFirst),
+            "deepening" => Ok(Deepening),
+            _ => Err(()),
+        }
+    }
+}
+
+pub enum Selection {
+    Uct,
+    Puct,
+}
+
+impl FromStr for Selection {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        use Selection::*;
+        match x {
+            "uct" => Ok(Uct),
+            "puct" => Ok(Puct),
+            _ => Err(()),
+        }
+    }
+}
+
+pub enum Restarts {
+    Never,
+    Luby,
+    Geometric,
+}
+
+impl FromStr for Restarts {
+    type Err = ();
+
+    fn from_str(x: &str) -> Result<Self, Self::Err> {
+        use Restarts::*;
+        match x {
+            "none" => Ok(Never),
+            "luby" => Ok(Luby),
+            "geometric" => Ok(Geometric),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Node {
+    parents: Vec<Id<Formula>>,
+    children: Option<Vec<IdSet<Formula>>>,
+    priors: Option<Vec<Score>>,
+    score: Score,
+    status: Status,
+    visits: usize,
+    proof: usize,
+    disproof: usize,
+    /// when the node was found unsatisfiable, counting from 1
+    refuted: usize,
+    /// inferences from the root when the node was first reached
+    depth: usize,
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            parents: vec![],
+            children: None,
+            priors: None,
+            score: 0.5.into(),
+            status: Status::Unknown,
+            visits: 0,
+            proof: 1,
+            disproof: 1,
+            refuted: 0,
+            depth: 0,
+        }
+    }
+}
+
+fn uct(
+    exploration: f32,
+    parent_visits: usize,
+    child_visits: usize,
+    score: Score,
+) -> Score {
+    // visits are reset to 0 on restart
+    let parent_visits = parent_visits.max(1) as f32;
+    let child_visits = (child_visits + 1) as f32;
+    (score.0 + exploration * (parent_visits.ln() / child_visits).sqrt()).into()
+}
+
+fn puct(
+    exploration: f32,
+    parent_visits: usize,
+    child_visits: usize,
+    score: Score,
+    prior: Score,
+) -> Score {
+    let parent_visits = parent_visits as f32;
+    let child_visits = (child_visits + 1) as f32;
+    (score.0 + exploration * prior.0 * parent_visits.sqrt() / child_visits)
+        .into()
+}
+
+pub struct Search {
+    root: Id<Formula>,
+    pub nodes: HashMap<Id<Formula>, Node>,
+    in_flight: HashMap<Id<Formula>, Vec<Id<Formula>>>,
+    /// formulae kept through eviction, such as those the explorer is on
+    pinned: Vec<Id<Formula>>,
+    frontier: BinaryHeap<Candidate>,
+    queued: usize,
+    depth_limit: usize,
+    expansions: usize,
+    deepest: usize,
+    node_limit: Option<usize>,
+    next_memory_check: usize,
+    exploration: f32,
+    restarts: usize,
+    next_restart: usize,
+    seed: u64,
+    lemmas: Lemmas,
+    refutations: usize,
+}
+
+impl Search {
+    pub fn new(f: Id<Formula>) -> Self {
+        let root = f.clone();
+        let nodes = HashMap::new();
+        let in_flight = HashMap::new();
+        let pinned = vec![];
+        let frontier = BinaryHeap::new();
+        let queued = 0;
+        let depth_limit = 0;
+        let expansions = 0;
+        let deepest = 0;
+        let node_limit = None;
+        let next_memory_check = evict::CHECK_INTERVAL;
+        let exploration = OPTIONS.exploration;
+        let restarts = 0;
+        let next_restart = restart::restart_interval(1);
+        let seed = 0x2545_f491_4f6c_dd1d;
+        let lemmas = Lemmas::default();
+        let refutations = 0;
+        let mut new = Self {
+            nodes,
+            root,
+            in_flight,
+            pinned,
+            frontier,
+            queued,
+            depth_limit,
+            expansions,
+            deepest,
+            node_limit,
+            next_memory_check,
+            exploration,
+            restarts,
+            next_restart,
+            seed,
+            lemmas,
+            refutations,
+        };
+        new.insert_node(f);
+        new
+    }
+
+    pub fn root(&self) -> &Id<Formula> {
+        &self.root
+    }
+
+    pub fn status(&self) -> Status {
+        self.node(&self.root).status
+    }
+
+    pub fn set_status(&mut self, f: &Id<Formula>, new_status: Status) {
+        if !self.nodes.contains_key(f) {
+            return;
+        }
+        let old_status = self.node_status(f);
+        if !old_status.is_known() && old_status != new_status {
+            record(f, new_status);
+            self.settle(f, new_status);
+            self.propagate_status(f);
+            self.update_numbers(f);
+        }
+    }
+
+    pub fn set_score(&mut self, f: &Id<Formula>, score: Score) {
+        if self.nodes.contains_key(f) && !self.node_status(f).is_known() {
+            self.node_mut(f).score = score;
+            self.propagate_score(f);
+            self.push_frontier(f);
+        }
+    }
+
+    /// Set prior weights for the inferences of `f`, keyed by inference.
+    ///
+    /// These are multiplied by any priors already set, so heuristic priors
+    /// refine those from rule weights rather than replacing them.
+    /// Priors for other than exactly the current inferences, such as those
+    /// arriving after `f` was evicted and re-expanded, are ignored.
+    pub fn set_priors(
+        &mut self,
+        f: &Id<Formula>,
+        priors: HashMap<IdSet<Formula>, Score>,
+    ) {
+        let node = match self.nodes.get(f) {
+            Some(node) => node,
+            None => return,
+        };
+        let children = match node.children {
+            Some(ref children)
+                if children.len() == priors.len()
+                    && children.iter().all(|c| priors.contains_key(c)) =>
+            {
+                children
+            }
+            _ => return,
+        };
+        let new = children.iter().map(|inference| priors[inference].0);
+        let priors = match node.priors {
+            Some(ref old) => {
+                old.iter().zip(new).map(|(p, q)| p.0 * q).collect()
+            }
+            None => new.collect::<Vec<_>>(),
+        };
+        let total: f32 = priors.iter().sum();
+        if total > 0.0 {
+            let normalised = priors.iter().map(|p| (p / total).into());
+            self.node_mut(f).priors = Some(normalised.collect());
+            self.order_by_priors(f);
+        }
+    }
+
+    /// Keep `formulae` in the search through eviction, instead of any
+    /// previously pinned.
+    pub fn pin(&mut self, formulae: Vec<Id<Formula>>) {
+        self.pinned = formulae;
+    }
+
+    pub fn inferences(&self, f: &Id<Formula>) -> Option<&Vec<IdSet<Formula>>> {
+        self.nodes.get(f).and_then(|node| node.children.as_ref())
+    }
+
+    /// Select a leaf to expand, if one is not already being expanded and
+    /// the root is not yet settled.
+    ///
+    /// Virtual loss is applied to the selected path until `finish_step`.
+    pub fn begin_step(&mut self) -> Option<Id<Formula>> {
+        if self.status().is_known() {
+            return None;
+        }
+        let path = self.select()?;
+        let leaf = path.last().expect("empty path").clone();
+        assert!(!self.node_status(&leaf).is_known());
+
+        if self.in_flight.contains_key(&leaf) {
+            self.remove_virtual_loss(&path);
+            None
+        } else {
+            self.in_flight.insert(leaf.clone(), path);
+            self.update_numbers(&leaf);
+            Some(leaf)
+        }
+    }
+
+    /// Attach the inferences `deduced` from `leaf` to the graph.
+    pub fn finish_step(
+        &mut self,
+        leaf: &Id<Formula>,
+        deduced: HashMap<IdSet<Formula>, f32>,
+    ) -> Vec<Id<Formula>> {
+        let path = self.in_flight.remove(leaf).expect("leaf not in flight");
+        self.remove_virtual_loss(&path);
+        self.attach(leaf, deduced)
+    }
+
+    /// Expand `leaf` immediately, bypassing selection.
+    pub fn expand_now(&mut self, leaf: &Id<Formula>) -> Vec<Id<Formula>> {
+        if self.node(leaf).children.is_some() {
+            return vec![];
+        }
+        self.attach(leaf, deductions(leaf))
+    }
+
+    fn attach(
+        &mut self,
+        leaf: &Id<Formula>,
+        deduced: HashMap<IdSet<Formula>, f32>,
+    ) -> Vec<Id<Formula>> {
+        if self.node_status(leaf).is_known() {
+            return vec![];
+        }
+
+        let mut ancestors: HashSet<_> =
+            self.ancestors_of(leaf).into_iter().collect();
+        ancestors.insert(leaf.clone());
+        let new_formulae = self.expand(leaf, deduced, &ancestors);
+
+        if self.node_status(leaf).is_known() {
+            self.propagate_status(leaf);
+        }
+        self.update_numbers(leaf);
+        self.expansions += 1;
+        self.check_memory();
+        self.check_restart();
+
+        new_formulae
+    }
+
+    /// How many leaves have been expanded
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// Greatest depth at which a node was first reached: cheap enough to
+    /// report while the search runs, unlike `max_depth`
+    pub fn deepest(&self) -> usize {
+        self.deepest
+    }
+
+    /// Length of the longest shortest path from the root to a node
+    pub fn max_depth(&self) -> usize {
+        self.depths().values().copied().max().unwrap_or(0)
+    }
+
+    /// Length of the shortest path from the root to each node
+    fn depths(&self) -> HashMap<&Id<Formula>, usize> {
+        let mut depths = HashMap::new();
+        let mut todo = VecDeque::new();
+        depths.insert(&self.root, 0);
+        todo.push_back(&self.root);
+        while let Some(next) = todo.pop_front() {
+            let depth = depths[next];
+            let children = match self.nodes.get(next) {
+                Some(Node {
+                    children: Some(children),
+                    ..
+                }) => children,
+                _ => continue,
+            };
+            for child in children.iter().flatten() {
+                if !depths.contains_key(child) {
+                    depths.insert(child, depth + 1);
+                    todo.push_back(child);
+                }
+            }
+        }
+        depths
+    }
+
+    pub fn proof(&self) -> Vec<Id<Formula>> {
+        assert_eq!(self.status(), Status::Unsat);
+        let mut log = vec![];
+        self.proof_of(&self.root, &mut log);
+        log
+    }
+
+    fn node(&self, f: &Id<Formula>) -> &Node {
+        self.nodes.get(f).expect("node did not exist")
+    }
+
+    fn node_mut(&mut self, f: &Id<Formula>) -> &mut Node {
+        self.nodes.get_mut(f).expect("node did not exist")
+    }
+
+    fn node_children(&self, f: &Id<Formula>) -> &Vec<IdSet<Formula>> {
+        self.node(f)
+            .children
+            .as_ref()
+            .expect("node had no children")
+    }
+
+    fn node_parents(&self, f: &Id<Formula>) -> &Vec<Id<Formula>> {
+        &self.node(f).parents
+    }
+
+    pub fn node_score(&self, f: &Id<Formula>) -> Score {
+        self.node(f).score
+    }
+
+    pub fn node_status(&self, f: &Id<Formula>) -> Status {
+        self.node(f).status
+    }
+
+    pub fn node_visits(&self, f: &Id<Formula>) -> usize {
+        self.node(f).visits
+    }
+
+    fn insert_node(&mut self, f: Id<Formula>) {
+        let status = self.lemma_status(&f);
+        self.nodes.insert(f.clone(), Node::default());
+        self.settle(&f, status);
+        if !status.is_known() {
+            self.open_lemma(&f);
+        }
+        self.push_frontier(&f);
+    }
+
+    fn ancestors_of(&self, leaf: &Id<Formula>) -> Vec<Id<Formula>> {
+        let mut done = HashSet::new();
+        let mut todo = VecDeque::new();
+        let mut ancestors = vec![];
+
+        done.insert(leaf);
+        todo.extend(self.node_parents(leaf).iter());
+
+        while let Some(next) = todo.pop_front() {
+            if !done.contains(&next) {
+                done.insert(next);
+                todo.extend(self.node_parents(next).iter());
+                ancestors.push(next.clone());
+            }
+        }
+
+        ancestors
+    }
+
+    fn proof_of(&self, f: &Id<Formula>, log: &mut Vec<Id<Formula>>) {
+        assert_eq!(self.node_status(f), Status::Unsat);
+        log.push(f.clone());
+        // only follow nodes refuted earlier: lemmas can justify each other
+        let refuted = self.node(f).refuted;
+        let earlier = |g: &Id<Formula>| {
+            self.node_status(g) == Status::Unsat
+                && self.node(g).refuted < refuted
+        };
+        let proved = self.node(f).children.as_ref().and_then(|children| {
+            children
+                .iter()
+                .find(|inference| inference.into_iter().all(earlier))
+        });
+        if let Some(inference) = proved {
+            for f in inference {
+                self.proof_of(f, log);
+            }
+        } else if let Some(lemma) = self.lemma_for(f, earlier) {
+            self.proof_of(lemma, log);
+        }
+    }
+
+    /// Give `f` its `status`, noting the order of refutations.
+    fn settle(&mut self, f: &Id<Formula>, status: Status) {
+        if status == Status::Unsat {
+            self.refutations += 1;
+            self.node_mut(f).refuted = self.refutations;
+        }
+        self.node_mut(f).status = status;
+    }
+
+    fn computed_status(&self, f: &Id<Formula>) -> Status {
+        if *f == Id::new(Formula::F) {
+            Status::Unsat
+        } else {
+            self.node_children(f)
+                .iter()
+                .map(|inference| {
+                    inference.into_iter().map(|f| self.node_status(f)).product()
+                })
+                .sum()
+        }
+    }
+
+    fn propagate_status(&mut self, start: &Id<Formula>) {
+        // parents are revisited whenever a child changes:
+        // ancestor order alone is not topological in a DAG
+        let mut todo: VecDeque<_> =
+            self.node_parents(start).iter().cloned().collect();
+        let mut learned = vec![start.clone()];
+        loop {
+            while let Some(f) = learned.pop() {
+                for (g, status) in self.learn_lemma(&f) {
+                    if !self.node_status(&g).is_known() {
+                        record(&g, status);
+                        self.settle(&g, status);
+                        self.update_numbers(&g);
+                        todo.extend(self.node_parents(&g).iter().cloned());
+                        learned.push(g);
+                    }
+                }
+            }
+
+            let f = match todo.pop_front() {
+                Some(f) => f,
+                None => break,
+            };
+            let old_status = self.node(&f).status;
+            let new_status = self.computed_status(&f);
+            if new_status.is_known() && old_status != new_status {
+                record(&f, new_status);
+                self.settle(&f, new_status);
+                todo.extend(self.node_parents(&f).iter().cloned());
+                learned.push(f);
+            }
+        }
+    }
+
+    /// The score of expanded `f`, from the scores of its children
+    fn backed_up_score(&self, f: &Id<Formula>) -> Score {
+        use Status::*;
+        self.node_children(f)
+            .iter()
+            .map(|inference| {
+                let mut total = Score::default();
+                let mut count = 0;
+                for f in inference {
+                    let status = self.node_status(f);
+                    match status {
+                        Sat => return 0.into(),
+                        Unsat => {
+                            continue;
+                        }
+                        Unknown => {
+                            total += self.node_score(f);
+                            count += 1;
+                        }
+                    }
+                }
+                if count != 0 {
+                    total / count.into()
+                } else {
+                    1.into()
+                }
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn propagate_score(&mut self, start: &Id<Formula>) {
+        for f in self.ancestors_of(start) {
+            self.node_mut(&f).score = self.backed_up_score(&f);
+        }
+    }
+
+    fn remove_virtual_loss(&mut self, path: &[Id<Formula>]) {
+        for f in path {
+            self.node_mut(f).visits -= VIRTUAL_LOSS;
+        }
+    }
+
+    fn select(&mut self) -> Option<Vec<Id<Formula>>> {
+        let path = match OPTIONS.search {
+            Strategy::Mcts | Strategy::Pns => self.descend(),
+            Strategy::BestFirst => vec![self.best_leaf()?],
+            Strategy::Deepening => self.deepening_path()?,
+        };
+        for f in &path {
+            self.node_mut(f).visits += 1 + VIRTUAL_LOSS;
+        }
+        Some(path)
+    }
+
+    fn descend(&self) -> Vec<Id<Formula>> {
+        let mut current = self.root.clone();
+        let mut path = vec![];
+
+        while self.node(&current).children.is_some() {
+            assert_eq!(self.node_status(&current), Status::Unknown);
+
+            let selected = match OPTIONS.search {
+                Strategy::Pns => self.select_most_proving(&current),
+                _ => self.select_uct(&current),
+            };
+            path.push(current);
+            current = selected;
+        }
+
+        path.push(current);
+        path
+    }
+
+    fn select_uct(&self, current: &Id<Formula>) -> Id<Formula> {
+        let parent_visits = self.node_visits(current);
+        let children = self.node_children(current);
+        let uniform = Score::from(1.0 / children.len() as f32);
+        let priors = self.node(current).priors.as_ref();
+        let possible = children.iter().enumerate().filter(|(_, inference)| {
+            !inference
+                .into_iter()
+                .any(|f| self.node_status(f) == Status::Sat)
+        });
+        let (_, selected_inference) = possible
+            .take(widening::visible(parent_visits))
+            .max_by_key(|(index, inference)| {
+                let score = inference
+                    .into_iter()
+                    .map(|f| self.node_score(f))
+                    .min()
+                    .expect("inference had no children");
+                let child_visits =
+                    inference.into_iter().map(|f| self.node_visits(f)).sum();
+                match OPTIONS.selection {
+                    Selection::Uct => uct(
+                        self.exploration,
+                        parent_visits,
+                        child_visits,
+                        score,
+                    ),
+                    Selection::Puct => {
+                        let prior = priors
+                            .and_then(|priors| priors.get(*index))
+                            .copied()
+                            .unwrap_or(uniform);
+                        puct(
+                            self.exploration,
+                            parent_visits,
+                            child_visits,
+                            score,
+                            prior,
+                        )
+                    }
+                }
+            })
+            .expect("no valid inferences");
+        let possible_formulae = selected_inference
+            .into_iter()
+            .filter(|f| !self.node_status(f).is_known());
+        possible_formulae
+            .min_by_key(|f| self.node_score(f))
+            .expect("inference had no children")
+            .clone()
+    }
+
+    fn expand(
+        &mut self,
+        leaf: &Id<Formula>,
+        deduced: HashMap<IdSet<Formula>, f32>,
+        filter: &HashSet<Id<Formula>>,
+    ) -> Vec<Id<Formula>> {
+        let mut new_formulae = vec![];
+        // alpha-equivalent formulae share a node
+        let mut weights: HashMap<IdSet<_>, f32> = HashMap::new();
+        for (inference, weight) in deduced {
+            let inference = inference.into_iter().map(|f| canonical(&f));
+            let entry = weights.entry(inference.collect()).or_insert(weight);
+            *entry = entry.max(weight);
+        }
+        let mut deduced = weights
+            .keys()
+            .filter(|inference| {
+                !inference.into_iter().any(|f| filter.contains(f))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if OPTIONS.deterministic {
+            deduced.sort_by_cached_key(|inference| {
+                inference.into_iter().map(identify).collect::<Vec<_>>()
+            });
+        }
+        widening::order_by_size(&mut deduced);
+
+        let depth = self.node(leaf).depth + 1;
+        for inference in &deduced {
+            for f in inference.into_iter() {
+                if !self.nodes.contains_key(f) {
+                    self.insert_node(f.clone());
+                    self.node_mut(f).depth = depth;
+                    self.deepest = self.deepest.max(depth);
+                    new_formulae.push(f.clone());
+                }
+
+                let parents = self.node_parents(f);
+                if !parents.contains(leaf) {
+                    self.node_mut(f).parents.push(leaf.clone());
+                }
+            }
+        }
+
+        // rule weights are the priors, refined by any from the heuristic
+        let priors = deduced
+            .iter()
+            .map(|inference| {
+                (inference.clone(), Score::from(weights[inference]))
+            })
+            .collect::<HashMap<_, _>>();
+        let uniform = deduced
+            .windows(2)
+            .all(|pair| priors[&pair[0]] == priors[&pair[1]]);
+        self.node_mut(leaf).children = Some(deduced);
+        if !uniform {
+            self.set_priors(leaf, priors);
+        }
+        self.settle(leaf, self.computed_status(leaf));
+        new_formulae
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::search::{Search, Strategy, VIRTUAL_LOSS};
+use crate::status::Status;
+
+/// Proof and disproof numbers saturate here
+pub const INFINITE: usize = usize::MAX;
+
+impl Search {
+    /// Recompute proof and disproof numbers from `start` upwards.
+    ///
+    /// Only maintained when running proof-number search.
+    pub(super) fn update_numbers(&mut self, start: &Id<Formula>) {
+        match OPTIONS.search {
+            Strategy::Pns => {}
+            _ => return,
+        }
+
+        let mut todo = VecDeque::new();
+        todo.push_back(start.clone());
+        while let Some(f) = todo.pop_front() {
+            let numbers = self.computed_numbers(&f);
+            let node = self.node_mut(&f);
+            if (node.proof, node.disproof) != numbers || f == *start {
+                node.proof = numbers.0;
+                node.disproof = numbers.1;
+                todo.extend(node.parents.iter().cloned());
+            }
+        }
+    }
+
+    /// Descend into the most-proving inference.
+    ///
+    /// Inferences are OR-nodes (minimise proof number),
+    /// their formulae AND-nodes (minimise disproof number).
+    /// Ties are broken in favour of less-visited nodes.
+    pub(super) fn select_most_proving(
+        &self,
+        current: &Id<Formula>,
+    ) -> Id<Formula> {
+        let selected_inference = self
+            .node_children(current)
+            .iter()
+            .filter(|inference| {
+                !inference
+                    .into_iter()
+                    .any(|f| self.node_status(f) == Status::Sat)
+            })
+            .min_by_key(|inference| {
+                let proof = inference
+                    .into_iter()
+                    .map(|f| self.node(f).proof)
+                    .fold(0, usize::saturating_add);
+                let visits: usize =
+                    inference.into_iter().map(|f| self.node_visits(f)).sum();
+                (proof, visits)
+            })
+            .expect("no valid inferences");
+
+        selected_inference
+            .into_iter()
+            .filter(|f| !self.node_status(f).is_known())
+            .min_by_key(|f| (self.node(f).disproof, self.node_visits(f)))
+            .expect("inference had no children")
+            .clone()
+    }
+
+    fn computed_numbers(&self, f: &Id<Formula>) -> (usize, usize) {
+        use Status::*;
+        let node = self.node(f);
+        match node.status {
+            Unsat => (0, INFINITE),
+            Sat => (INFINITE, 0),
+            Unknown => match node.children.as_ref() {
+                // other threads should prefer leaves not already in flight
+                None if self.in_flight.contains_key(f) => {
+                    (1 + VIRTUAL_LOSS, 1 + VIRTUAL_LOSS)
+                }
+                None => (1, 1),
+                Some(children) => {
+                    let mut proof = INFINITE;
+                    let mut disproof = 0usize;
+                    for inference in children {
+                        let inference_proof = inference
+                            .into_iter()
+                            .map(|f| self.node(f).proof)
+                            .fold(0, usize::saturating_add);
+                        let inference_disproof = inference
+                            .into_iter()
+                            .map(|f| self.node(f).disproof)
+                            .min()
+                            .unwrap_or(INFINITE);
+                        proof = proof.min(inference_proof);
+                        disproof = disproof.saturating_add(inference_disproof);
+                    }
+                    (proof, disproof)
+                }
+            },
+        }
+    }
+}
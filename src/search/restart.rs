@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use crate::options::OPTIONS;
+use crate::search::{Node, Restarts, Search, Strategy, VIRTUAL_LOSS};
+
+/// The `i`th element (from 1) of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ...
+fn luby(i: usize) -> usize {
+    let mut k = 1;
+    while (1 << k) - 1 < i {
+        k += 1;
+    }
+    if i == (1 << k) - 1 {
+        1 << (k - 1)
+    } else {
+        luby(i - (1 << (k - 1)) + 1)
+    }
+}
+
+/// Expansions between restart `n - 1` and restart `n`
+pub(super) fn restart_interval(n: usize) -> usize {
+    let base = OPTIONS.restart_interval;
+    match OPTIONS.restarts {
+        Restarts::Never => usize::MAX,
+        Restarts::Luby => base.saturating_mul(luby(n)),
+        Restarts::Geometric => {
+            let bits = std::mem::size_of::<usize>() * 8;
+            let doublings = (n - 1).min(bits - 1);
+            base.saturating_mul(1 << doublings)
+        }
+    }
+}
+
+impl Search {
+    /// Restart if the schedule says so.
+    pub(super) fn check_restart(&mut self) {
+        if !matches!(OPTIONS.search, Strategy::Mcts)
+            || self.expansions < self.next_restart
+        {
+            return;
+        }
+
+        self.restart();
+        self.restarts += 1;
+        self.next_restart = self
+            .expansions
+            .saturating_add(restart_interval(self.restarts + 1));
+        log::debug!(
+            "restart {} after {} expansions, exploration {:.3}",
+            self.restarts,
+            self.expansions,
+            self.exploration
+        );
+    }
+
+    /// Forget visits, keeping the graph, any known statuses and the scores
+    /// of leaves, from which those of expanded nodes are backed up again.
+    ///
+    /// Virtual loss on paths still being expanded is kept, as it is removed
+    /// when they finish.
+    fn restart(&mut self) {
+        let fresh = Node::default();
+        for node in self.nodes.values_mut() {
+            node.visits = fresh.visits;
+        }
+        for path in self.in_flight.values() {
+            for f in path {
+                if let Some(node) = self.nodes.get_mut(f) {
+                    node.visits += VIRTUAL_LOSS;
+                }
+            }
+        }
+
+        self.rescore();
+
+        let perturbation = OPTIONS.restart_perturbation;
+        if perturbation > 0.0 {
+            let noise = 2.0 * self.random() - 1.0;
+            self.exploration =
+                OPTIONS.exploration * (1.0 + perturbation * noise).max(0.0);
+        }
+    }
+
+    /// Back up scores from the leaves, children before parents.
+    fn rescore(&mut self) {
+        let mut done = HashSet::new();
+        let mut todo = vec![(self.root.clone(), false)];
+        while let Some((f, children_done)) = todo.pop() {
+            if children_done {
+                self.node_mut(&f).score = self.backed_up_score(&f);
+                continue;
+            }
+            if self.node(&f).children.is_none() || !done.insert(f.clone()) {
+                continue;
+            }
+            todo.push((f.clone(), true));
+            for child in self.node_children(&f).iter().flatten() {
+                todo.push((child.clone(), false));
+            }
+        }
+    }
+
+    /// Pseudo-random number in [0, 1): xorshift, seeded identically each run
+    fn random(&mut self) -> f32 {
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        (x >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
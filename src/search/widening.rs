@@ -0,0 +1,56 @@
+use unique::Id;
+
+use crate::collections::IdSet;
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::search::Search;
+
+/// How many inferences MCTS considers at a node visited `visits` times
+pub(super) fn visible(visits: usize) -> usize {
+    match OPTIONS.widening {
+        Some(constant) => {
+            let widened = constant
+                * (visits.max(1) as f32).powf(OPTIONS.widening_exponent);
+            (widened.ceil() as usize).max(1)
+        }
+        None => usize::MAX,
+    }
+}
+
+/// Put fresh inferences in order of total size, smallest first, so that
+/// the simplest are visible first.
+pub(super) fn order_by_size(inferences: &mut [IdSet<Formula>]) {
+    if OPTIONS.widening.is_some() {
+        inferences.sort_by_cached_key(|inference| {
+            inference.into_iter().map(Formula::size).sum::<usize>()
+        });
+    }
+}
+
+impl Search {
+    /// Put the inferences of `f` in order of prior, most likely first.
+    pub(super) fn order_by_priors(&mut self, f: &Id<Formula>) {
+        if OPTIONS.widening.is_none() {
+            return;
+        }
+        let node = self.node_mut(f);
+        let (children, priors) =
+            match (node.children.take(), node.priors.take()) {
+                (Some(children), Some(priors))
+                    if children.len() == priors.len() =>
+                {
+                    (children, priors)
+                }
+                (children, priors) => {
+                    node.children = children;
+                    node.priors = priors;
+                    return;
+                }
+            };
+        let mut ordered = children.into_iter().zip(priors).collect::<Vec<_>>();
+        ordered.sort_by_key(|(_, prior)| std::cmp::Reverse(*prior));
+        let (children, priors) = ordered.into_iter().unzip();
+        node.children = Some(children);
+        node.priors = Some(priors);
+    }
+}
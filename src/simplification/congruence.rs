@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::ordering::greater;
+use crate::symbol::Symbol;
+use crate::term::Term;
+use Formula::*;
+
+fn ground(t: &Id<Term>) -> bool {
+    Term::breadth_first(t).all(|t| t.is_function())
+}
+
+/// The terms of `f`, if it is a ground equation
+fn ground_equation(f: &Id<Formula>) -> Option<&[Id<Term>]> {
+    match **f {
+        Eq(ref ts) if ts.into_iter().all(ground) => Some(&ts.as_ref()[..]),
+        _ => None,
+    }
+}
+
+/// Congruence closure of ground equations, by union-find.
+///
+/// The root of each class is its least member.
+#[derive(Default)]
+struct Closure {
+    parents: HashMap<Id<Term>, Id<Term>>,
+}
+
+impl Closure {
+    fn add(&mut self, t: &Id<Term>) {
+        for t in Term::breadth_first(t) {
+            self.parents.entry(t.clone()).or_insert_with(|| t.clone());
+        }
+    }
+
+    fn find(&self, t: &Id<Term>) -> Id<Term> {
+        let mut t = t;
+        loop {
+            let parent = &self.parents[t];
+            if parent == t {
+                return t.clone();
+            }
+            t = parent;
+        }
+    }
+
+    /// Merge the classes of `s` and `t`, returning whether they differed.
+    fn union(&mut self, s: &Id<Term>, t: &Id<Term>) -> bool {
+        let s = self.find(s);
+        let t = self.find(t);
+        if s == t {
+            return false;
+        }
+        if greater(&s, &t) {
+            self.parents.insert(s, t);
+        } else {
+            self.parents.insert(t, s);
+        }
+        true
+    }
+
+    /// Merge classes of terms with the same symbol and equal arguments.
+    fn close(&mut self) {
+        loop {
+            let mut signatures: HashMap<(Id<Symbol>, Vec<Id<Term>>), _> =
+                HashMap::new();
+            let mut merged = false;
+            let terms = self.parents.keys().cloned().collect::<Vec<_>>();
+            for t in terms {
+                if let Term::Fn(ref f, ref ts) = *t {
+                    let arguments = ts.iter().map(|t| self.find(t)).collect();
+                    let signature = (f.clone(), arguments);
+                    match signatures.get(&signature).cloned() {
+                        Some(u) => merged |= self.union(&t, &u),
+                        None => {
+                            signatures.insert(signature, t.clone());
+                        }
+                    }
+                }
+            }
+            if !merged {
+                return;
+            }
+        }
+    }
+
+    /// `t` with each ground subterm replaced by its least equal term
+    fn normalise(
+        &self,
+        t: &Id<Term>,
+        memo: &mut HashMap<Id<Term>, Id<Term>>,
+    ) -> Id<Term> {
+        if let Some(normal) = memo.get(t) {
+            return normal.clone();
+        }
+        let rebuild = |t: &Id<Term>, memo: &mut HashMap<_, _>| match **t {
+            Term::Var(_) => t.clone(),
+            Term::Fn(ref f, ref ts) => Id::new(Term::Fn(
+                f.clone(),
+                ts.iter().map(|t| self.normalise(t, memo)).collect(),
+            )),
+        };
+        let normal = if self.parents.contains_key(t) {
+            rebuild(&self.find(t), memo)
+        } else {
+            let rebuilt = rebuild(t, memo);
+            if &rebuilt != t && self.parents.contains_key(&rebuilt) {
+                self.normalise(&rebuilt, memo)
+            } else {
+                rebuilt
+            }
+        };
+        memo.insert(t.clone(), normal.clone());
+        normal
+    }
+}
+
+/// Rewrite ground terms of conjuncts to representatives of their classes
+/// under the ground equations among them, detecting contradictions.
+///
+/// The equations themselves are kept as they are.
+pub fn simplify_congruence(f: &Id<Formula>) -> Id<Formula> {
+    let ps = match **f {
+        And(ref ps) => ps,
+        _ => return f.clone(),
+    };
+    let equations = ps.into_iter().filter_map(ground_equation);
+    let equations = equations.collect::<Vec<_>>();
+    if equations.is_empty() {
+        return f.clone();
+    }
+    let disequations = ps
+        .into_iter()
+        .filter_map(|p| match **p {
+            Not(ref p) => ground_equation(p),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut closure = Closure::default();
+    for ts in equations.iter().chain(disequations.iter()) {
+        for t in *ts {
+            closure.add(t);
+        }
+    }
+    for ts in &equations {
+        for pair in ts.windows(2) {
+            closure.union(&pair[0], &pair[1]);
+        }
+    }
+    closure.close();
+
+    let contradiction = disequations
+        .iter()
+        .any(|ts| ts.iter().all(|t| closure.find(t) == closure.find(&ts[0])));
+    if contradiction {
+        return Id::new(F);
+    }
+
+    let mut memo = HashMap::new();
+    Id::new(And(ps
+        .into_iter()
+        .map(|p| {
+            if ground_equation(p).is_some() {
+                p.clone()
+            } else {
+                Formula::map_terms(p, &mut |t| closure.normalise(t, &mut memo))
+            }
+        })
+        .collect()))
+}
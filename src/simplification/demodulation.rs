@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::ordering::greater;
+use crate::term::Term;
+use Formula::*;
+
+/// An oriented unit equation `from = to`, from its conjunct
+struct Rule<'a> {
+    conjunct: &'a Id<Formula>,
+    from: &'a Id<Term>,
+    to: &'a Id<Term>,
+}
+
+/// Whether each variable of `t` is one of the `bound` innermost
+fn closed_under(t: &Id<Term>, bound: usize) -> bool {
+    Term::breadth_first(t).all(|t| match **t {
+        Term::Var(n) => n < bound,
+        Term::Fn(_, _) => true,
+    })
+}
+
+fn rule(f: &Id<Formula>) -> Option<Rule<'_>> {
+    let mut bound = 0;
+    let mut body = f;
+    while let All(ref p) = **body {
+        bound += 1;
+        body = p;
+    }
+    let (s, t) = match **body {
+        Eq(ref ts) if ts.len() == 2 => (&ts.as_ref()[0], &ts.as_ref()[1]),
+        _ => return None,
+    };
+    // variables bound outside the conjunction are not universal
+    if !closed_under(s, bound) || !closed_under(t, bound) {
+        return None;
+    }
+    let (from, to) = if greater(s, t) {
+        (s, t)
+    } else if greater(t, s) {
+        (t, s)
+    } else {
+        return None;
+    };
+    Some(Rule {
+        conjunct: f,
+        from,
+        to,
+    })
+}
+
+/// Bind variables of `pattern` so that it becomes `t`.
+///
+/// Variables of `t` are treated as constants.
+fn matches(
+    bindings: &mut HashMap<usize, Id<Term>>,
+    pattern: &Id<Term>,
+    t: &Id<Term>,
+) -> bool {
+    use Term::*;
+    match (&**pattern, &**t) {
+        (Var(n), _) => match bindings.get(n) {
+            Some(bound) => bound == t,
+            None => {
+                bindings.insert(*n, t.clone());
+                true
+            }
+        },
+        (Fn(f, ps), Fn(g, ts)) => {
+            f == g
+                && ps.len() == ts.len()
+                && ps
+                    .iter()
+                    .zip(ts.iter())
+                    .all(|(p, t)| matches(bindings, p, t))
+        }
+        _ => false,
+    }
+}
+
+fn substitute(t: &Id<Term>, bindings: &HashMap<usize, Id<Term>>) -> Id<Term> {
+    use Term::*;
+    match **t {
+        Var(n) => bindings[&n].clone(),
+        Fn(ref f, ref ts) => Id::new(Fn(
+            f.clone(),
+            ts.iter().map(|t| substitute(t, bindings)).collect(),
+        )),
+    }
+}
+
+/// The normal form of `t`, rewriting innermost first
+fn normalise(t: &Id<Term>, rules: &[Rule]) -> Id<Term> {
+    let t = match **t {
+        Term::Var(_) => return t.clone(),
+        Term::Fn(ref f, ref ts) => Id::new(Term::Fn(
+            f.clone(),
+            ts.iter().map(|t| normalise(t, rules)).collect(),
+        )),
+    };
+    for rule in rules {
+        let mut bindings = HashMap::new();
+        if matches(&mut bindings, rule.from, &t) {
+            return normalise(&substitute(rule.to, &bindings), rules);
+        }
+    }
+    t
+}
+
+/// Rewrite conjuncts with the oriented unit equations among them.
+///
+/// Equations are not rewritten by each other: that could lose one of them.
+pub fn simplify_demodulation(f: &Id<Formula>) -> Id<Formula> {
+    let ps = match **f {
+        And(ref ps) => ps,
+        _ => return f.clone(),
+    };
+    let rules = ps.into_iter().filter_map(rule).collect::<Vec<_>>();
+    if rules.is_empty() {
+        return f.clone();
+    }
+
+    Id::new(And(ps
+        .into_iter()
+        .map(|p| {
+            if rules.iter().any(|rule| rule.conjunct == p) {
+                p.clone()
+            } else {
+                Formula::map_terms(p, &mut |t| normalise(t, &rules))
+            }
+        })
+        .collect()))
+}
@@ -1,10 +1,15 @@
+mod congruence;
+mod demodulation;
 mod equational;
 mod propositional;
+mod quantifier;
 
 use unique::Id;
 
 use crate::formula::Formula;
-use crate::system::check_for_timeout;
+use crate::system::within_time;
+
+pub use quantifier::miniscope;
 
 fn simplify_children(f: &Id<Formula>) -> Id<Formula> {
     use Formula::*;
@@ -23,17 +28,23 @@ fn simplify_children(f: &Id<Formula>) -> Id<Formula> {
 fn simplify_step(f: &Id<Formula>) -> Id<Formula> {
     let f = simplify_children(f);
     let f = propositional::simplify_propositional(&f);
-    equational::simplify_equational(&f)
+    let f = quantifier::simplify_quantifier(&f);
+    let f = equational::simplify_equational(&f);
+    let f = congruence::simplify_congruence(&f);
+    demodulation::simplify_demodulation(&f)
 }
 
+/// Simplify to a fixpoint, or as far as possible within the time limit.
+///
+/// Callers are responsible for noticing the time out:
+/// the search must stop cleanly to save its state.
 pub fn simplify(f: &Id<Formula>) -> Id<Formula> {
     let mut f = f.clone();
 
     loop {
         let simplified = simplify_step(&f);
-        check_for_timeout();
-        if f == simplified {
-            return f;
+        if f == simplified || !within_time() {
+            return simplified;
         }
         f = simplified;
     }
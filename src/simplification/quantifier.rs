@@ -0,0 +1,95 @@
+use unique::Id;
+
+use crate::formula::Formula;
+use crate::options::OPTIONS;
+use crate::term::Term;
+use Formula::*;
+
+/// Remove a binder for variable `n`, which does not occur in `f`.
+fn lower(f: &Id<Formula>, n: usize) -> Id<Formula> {
+    match **f {
+        T | F => f.clone(),
+        Eq(_) | Prd(_, _) => Formula::map_terms(f, &mut |t| {
+            Term::map_variables(t, &mut |m| if m > n { m - 1 } else { m })
+        }),
+        Not(ref p) => Id::new(Not(lower(p, n))),
+        Imp(ref p, ref q) => Id::new(Imp(lower(p, n), lower(q, n))),
+        Or(ref ps) => {
+            Id::new(Or(ps.into_iter().map(|p| lower(p, n)).collect()))
+        }
+        And(ref ps) => {
+            Id::new(And(ps.into_iter().map(|p| lower(p, n)).collect()))
+        }
+        Eqv(ref ps) => {
+            Id::new(Eqv(ps.into_iter().map(|p| lower(p, n)).collect()))
+        }
+        All(ref p) => Id::new(All(lower(p, n + 1))),
+        Ex(ref p) => Id::new(Ex(lower(p, n + 1))),
+    }
+}
+
+/// Quantify `body`, after miniscoping, as narrowly as possible.
+fn scope(universal: bool, body: &Id<Formula>) -> Id<Formula> {
+    let quantify = |p| Id::new(if universal { All(p) } else { Ex(p) });
+    if !Formula::occurs(body, 0) {
+        return lower(body, 0);
+    }
+    match (&**body, universal) {
+        (And(ref ps), true) => {
+            Id::new(And(ps.into_iter().map(|p| scope(true, p)).collect()))
+        }
+        (Or(ref ps), false) => {
+            Id::new(Or(ps.into_iter().map(|p| scope(false, p)).collect()))
+        }
+        (And(ref ps), false) | (Or(ref ps), true) => {
+            let (bound, free): (Vec<_>, Vec<_>) =
+                ps.into_iter().cloned().partition(|p| Formula::occurs(p, 0));
+            if free.is_empty() {
+                return quantify(body.clone());
+            }
+            let free = free.iter().map(|p| lower(p, 0));
+            let rebuild =
+                |ps| Id::new(if universal { Or(ps) } else { And(ps) });
+            let scoped = if bound.len() == 1 {
+                scope(universal, &bound[0])
+            } else {
+                quantify(rebuild(bound.into_iter().collect()))
+            };
+            rebuild(free.chain(std::iter::once(scoped)).collect())
+        }
+        _ => quantify(body.clone()),
+    }
+}
+
+/// Push quantifiers in `f` inwards, as far as `And` and `Or` allow.
+pub fn miniscope(f: &Id<Formula>) -> Id<Formula> {
+    match **f {
+        T | F | Prd(_, _) | Eq(_) => f.clone(),
+        Not(ref p) => Id::new(Not(miniscope(p))),
+        Imp(ref p, ref q) => Id::new(Imp(miniscope(p), miniscope(q))),
+        And(ref ps) => Id::new(And(ps.into_iter().map(miniscope).collect())),
+        Or(ref ps) => Id::new(Or(ps.into_iter().map(miniscope).collect())),
+        Eqv(ref ps) => Id::new(Eqv(ps.into_iter().map(miniscope).collect())),
+        All(ref p) => scope(true, &miniscope(p)),
+        Ex(ref p) => scope(false, &miniscope(p)),
+    }
+}
+
+/// Drop vacuous quantifiers and push others inwards one step at a time.
+///
+/// Clausified problems are only stripped of vacuous quantifiers, so that
+/// clauses stay universally closed disjunctions.
+pub fn simplify_quantifier(f: &Id<Formula>) -> Id<Formula> {
+    let (universal, p) = match **f {
+        All(ref p) => (true, p),
+        Ex(ref p) => (false, p),
+        _ => return f.clone(),
+    };
+    if !OPTIONS.clausify {
+        scope(universal, p)
+    } else if Formula::occurs(p, 0) {
+        f.clone()
+    } else {
+        lower(p, 0)
+    }
+}
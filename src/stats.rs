@@ -0,0 +1,110 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::time::Duration;
+
+use crate::heuristic;
+use crate::options::OPTIONS;
+use crate::oracle;
+use crate::search::Search;
+use crate::status::Status;
+use crate::system::{elapsed, memory_usage};
+
+/// How often progress is logged
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lengths of the queues between the search and its helpers
+#[derive(Default, Clone, Copy)]
+pub struct Queues {
+    pub oracle: usize,
+    pub heuristic: usize,
+}
+
+#[derive(Serialize)]
+struct Statistics<'a> {
+    file: &'a str,
+    status: Status,
+    seconds: f64,
+    nodes: usize,
+    expansions: usize,
+    expansions_per_second: f64,
+    max_depth: usize,
+    root_score: f32,
+    root_visits: usize,
+    oracle_consultations: usize,
+    heuristic_queries: usize,
+    max_oracle_queue: usize,
+    max_heuristic_queue: usize,
+    memory_bytes: Option<usize>,
+}
+
+/// Periodic reports on the search, and a summary at the end
+#[derive(Default)]
+pub struct Progress {
+    last_report: Duration,
+    last_expansions: usize,
+    max_queues: Queues,
+}
+
+impl Progress {
+    /// Whether a report is due
+    pub fn due(&self) -> bool {
+        elapsed() >= self.last_report + REPORT_INTERVAL
+    }
+
+    /// Log the state of `search`
+    pub fn report(&mut self, search: &Search, queues: Queues) {
+        let now = elapsed();
+        let seconds = now.checked_sub(self.last_report).unwrap_or_default();
+        let seconds = seconds.as_secs_f64();
+        let expansions = search.expansions() - self.last_expansions;
+        self.last_report = now;
+        self.last_expansions = search.expansions();
+        self.max_queues.oracle = self.max_queues.oracle.max(queues.oracle);
+        self.max_queues.heuristic =
+            self.max_queues.heuristic.max(queues.heuristic);
+
+        let root = search.root();
+        log::info!(
+            "{} node(s), {:.0} expansion(s)/s, depth {}, root score {:.3}, \
+             queued for oracle {}, heuristic {}",
+            search.nodes.len(),
+            expansions as f64 / seconds.max(f64::EPSILON),
+            search.deepest(),
+            search.node_score(root).0,
+            queues.oracle,
+            queues.heuristic,
+        );
+    }
+
+    /// Write a summary of the whole run to `path`, as JSON
+    pub fn write_statistics(
+        &self,
+        path: &str,
+        search: &Search,
+    ) -> io::Result<()> {
+        let seconds = elapsed().as_secs_f64();
+        let root = search.root();
+        let statistics = Statistics {
+            file: &OPTIONS.file,
+            status: search.status(),
+            seconds,
+            nodes: search.nodes.len(),
+            expansions: search.expansions(),
+            expansions_per_second: search.expansions() as f64
+                / seconds.max(f64::EPSILON),
+            max_depth: search.max_depth(),
+            root_score: search.node_score(root).0,
+            root_visits: search.node_visits(root),
+            oracle_consultations: oracle::consultations(),
+            heuristic_queries: heuristic::queries(),
+            max_oracle_queue: self.max_queues.oracle,
+            max_heuristic_queue: self.max_queues.heuristic,
+            memory_bytes: memory_usage(),
+        };
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, &statistics)?;
+        Ok(())
+    }
+}
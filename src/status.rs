@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::iter::{Product, Sum};
 use std::ops::{Add, Mul};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Status {
     Sat = 0,
     Unsat = 1,
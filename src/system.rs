@@ -1,8 +1,9 @@
 use lazy_static::lazy_static;
 use std::ffi::OsStr;
+use std::fs::read_to_string;
 use std::path::Path;
 use std::process::exit;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use unique::Id;
 
 use crate::formula::Formula;
@@ -38,6 +39,12 @@ pub fn time_out() -> ! {
     exit(1)
 }
 
+pub fn gave_up() -> ! {
+    println!();
+    println!("% SZS status GaveUp for {}", logical_data_id());
+    exit(1)
+}
+
 pub fn satisfiable() -> ! {
     let id = logical_data_id();
     println!();
@@ -60,15 +67,44 @@ pub fn unsatisfiable(lemmas: Vec<Id<Formula>>) -> ! {
     exit(0)
 }
 
+pub fn clausified(clauses: Vec<Id<Formula>>) -> ! {
+    let id = logical_data_id();
+    println!();
+    println!("% SZS status Success for {}", id);
+    println!("% SZS output start ListOfCNF for {}", id);
+    for clause in &clauses {
+        tptp::write_clause(&mut std::io::stdout(), clause)
+            .expect("writing clause to stdout failed");
+    }
+    println!("% SZS output end ListOfCNF for {}", id);
+    exit(0)
+}
+
 pub fn check_for_timeout() {
     if !within_time() {
         time_out()
     }
 }
 
+pub fn elapsed() -> Duration {
+    START_TIME.elapsed().unwrap_or_default()
+}
+
 pub fn within_time() -> bool {
-    let elapsed = START_TIME.elapsed().unwrap_or_default();
-    elapsed < OPTIONS.time
+    elapsed() < OPTIONS.time
+}
+
+/// Time left before the time limit, zero if it has passed
+pub fn remaining_time() -> Duration {
+    OPTIONS.time.checked_sub(elapsed()).unwrap_or_default()
+}
+
+/// Resident set size of this process in bytes, if the OS reports it
+pub fn memory_usage() -> Option<usize> {
+    let status = read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    Some(kilobytes << 10)
 }
 
 pub fn initialize() {
@@ -43,6 +43,43 @@ impl Term {
         }
     }
 
+    /// Replace variable `index` by the closed term `with`.
+    pub fn instantiate(
+        t: &Id<Term>,
+        index: usize,
+        with: &Id<Term>,
+    ) -> Id<Term> {
+        match **t {
+            Var(n) => {
+                if n == index {
+                    with.clone()
+                } else {
+                    t.clone()
+                }
+            }
+            Fn(ref f, ref ts) => Id::new(Fn(
+                f.clone(),
+                ts.iter()
+                    .map(|t| Self::instantiate(t, index, with))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Renumber the variables of `t` by `map`.
+    pub fn map_variables(
+        t: &Id<Term>,
+        map: &mut impl FnMut(usize) -> usize,
+    ) -> Id<Term> {
+        match **t {
+            Var(n) => Id::new(Var(map(n))),
+            Fn(ref f, ref ts) => Id::new(Fn(
+                f.clone(),
+                ts.iter().map(|t| Self::map_variables(t, map)).collect(),
+            )),
+        }
+    }
+
     pub fn replace(t: &Id<Term>, from: &Id<Term>, to: &Id<Term>) -> Id<Term> {
         if t == from {
             to.clone()
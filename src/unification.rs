@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use unique::Id;
+
+use crate::collections::IdList;
+use crate::formula::Formula;
+use crate::term::Term;
+
+use Term::*;
+
+/// Bindings of variables to terms, in triangular form.
+///
+/// Variables are de Bruijn indices, so terms to be unified must either
+/// share their binders or have been renamed apart by the caller.
+#[derive(Clone, Default, Debug)]
+pub struct Substitution {
+    bindings: HashMap<usize, Id<Term>>,
+}
+
+impl Substitution {
+    /// Follow bindings from `t` until it is not a bound variable.
+    fn walk<'a>(&'a self, mut t: &'a Id<Term>) -> &'a Id<Term> {
+        while let Var(n) = **t {
+            match self.bindings.get(&n) {
+                Some(bound) => t = bound,
+                None => break,
+            }
+        }
+        t
+    }
+
+    fn occurs(&self, n: usize, t: &Id<Term>) -> bool {
+        match **self.walk(t) {
+            Var(m) => n == m,
+            Fn(_, ref ts) => ts.iter().any(|t| self.occurs(n, t)),
+        }
+    }
+
+    fn bind(&mut self, n: usize, t: &Id<Term>) -> bool {
+        if self.occurs(n, t) {
+            return false;
+        }
+        self.bindings.insert(n, t.clone());
+        true
+    }
+
+    /// The term bound to variable `n`, with all bindings applied
+    pub fn binding(&self, n: usize) -> Option<Id<Term>> {
+        self.bindings.get(&n).map(|t| self.apply(t))
+    }
+
+    /// Apply all bindings to `t`.
+    pub fn apply(&self, t: &Id<Term>) -> Id<Term> {
+        let t = self.walk(t);
+        match **t {
+            Var(_) => t.clone(),
+            Fn(ref f, ref ts) => Id::new(Fn(
+                f.clone(),
+                ts.iter().map(|t| self.apply(t)).collect(),
+            )),
+        }
+    }
+
+    /// Extend the substitution to unify `s` and `t`, if possible.
+    ///
+    /// The substitution may be partially extended on failure.
+    pub fn unify(&mut self, s: &Id<Term>, t: &Id<Term>) -> bool {
+        let s = self.walk(s).clone();
+        let t = self.walk(t).clone();
+        if s == t {
+            return true;
+        }
+        match (&*s, &*t) {
+            (Var(n), _) => self.bind(*n, &t),
+            (_, Var(n)) => self.bind(*n, &s),
+            (Fn(f, ss), Fn(g, ts)) => f == g && self.unify_lists(ss, ts),
+        }
+    }
+
+    /// Extend the substitution to unify `ss` and `ts` pointwise.
+    pub fn unify_lists(
+        &mut self,
+        ss: &IdList<Term>,
+        ts: &IdList<Term>,
+    ) -> bool {
+        ss.len() == ts.len()
+            && ss.iter().zip(ts.iter()).all(|(s, t)| self.unify(s, t))
+    }
+}
+
+/// A most general unifier of `s` and `t`
+pub fn unify(s: &Id<Term>, t: &Id<Term>) -> Option<Substitution> {
+    let mut substitution = Substitution::default();
+    if substitution.unify(s, t) {
+        Some(substitution)
+    } else {
+        None
+    }
+}
+
+/// Most general unifiers of the atoms `p` and `q`.
+///
+/// Equations are unordered, so may unify in more than one way.
+pub fn unify_atoms(p: &Id<Formula>, q: &Id<Formula>) -> Vec<Substitution> {
+    use Formula::*;
+    match (&**p, &**q) {
+        (Prd(p, ss), Prd(q, ts)) if p == q => {
+            let mut substitution = Substitution::default();
+            if substitution.unify_lists(ss, ts) {
+                vec![substitution]
+            } else {
+                vec![]
+            }
+        }
+        (Eq(ss), Eq(ts)) if ss.len() == 2 && ts.len() == 2 => {
+            let (ss, ts) = (ss.as_ref(), ts.as_ref());
+            [(0, 1), (1, 0)]
+                .iter()
+                .filter_map(|&(i, j)| {
+                    let mut substitution = Substitution::default();
+                    if substitution.unify(&ss[0], &ts[i])
+                        && substitution.unify(&ss[1], &ts[j])
+                    {
+                        Some(substitution)
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}